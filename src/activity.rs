@@ -1,7 +1,10 @@
+use std::collections::HashSet;
+
+use crate::history::Session;
 use crate::sql;
 use crate::ActID;
 use crate::Conn;
-use uuid::{Uuid};
+use uuid::Uuid;
 
 #[derive(Clone, Debug)]
 pub struct Activity {
@@ -11,6 +14,94 @@ pub struct Activity {
     pub assigned: u32,
     pub parent: Option<ActID>,
     pub children: Vec<Activity>,
+    /// Archived activities are hidden from the tree and the ranking, and don't take part
+    /// in their siblings' assignments, but keep their history and notes.
+    pub archived: bool,
+    /// Unix time of when the activity was added.
+    pub created_at: u64,
+    /// Unix time of when its name, parent, assigned value or archived state last changed.
+    pub updated_at: u64,
+    /// Unix time of the last session logged on it or one of its descendants.
+    pub last_session_at: Option<u64>,
+}
+
+/// Where an activity is moved among its siblings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Reorder {
+    Up,
+    Down,
+    Top,
+    Bottom,
+}
+
+impl Reorder {
+    pub fn label(self) -> &'static str {
+        match self {
+            Reorder::Up => "up",
+            Reorder::Down => "down",
+            Reorder::Top => "to the top",
+            Reorder::Bottom => "to the bottom",
+        }
+    }
+}
+
+/// What the list of leaves is ordered by. Except for the priority, the most recent
+/// comes first, and leaves without a session come last.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Priority,
+    Created,
+    Updated,
+    LastSession,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 4] = [
+        SortKey::Priority,
+        SortKey::Created,
+        SortKey::Updated,
+        SortKey::LastSession,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Priority => "priority",
+            SortKey::Created => "created",
+            SortKey::Updated => "changed",
+            SortKey::LastSession => "last session",
+        }
+    }
+
+    /// The key after this one in [`SortKey::ALL`], wrapping around.
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|key| *key == self).unwrap();
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
+
+/// What's done with the children and sessions of a deleted activity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeleteMode {
+    /// Only the activity is deleted, its children move up to its parent. With
+    /// `merge_sessions` the sessions logged on it count as its parent's, otherwise
+    /// they're deleted.
+    Lift { merge_sessions: bool },
+    /// The activity is deleted with its descendants and all their sessions.
+    Subtree,
+}
+
+/// What deleting an activity would affect, to show before doing it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct DeleteImpact {
+    pub descendants: usize,
+    /// The sessions logged on the activity and its descendants.
+    pub sessions: usize,
+    /// The sessions logged on the activity itself.
+    pub direct_sessions: usize,
+    /// The notes of the activity and its descendants.
+    pub notes: usize,
+    pub has_note: bool,
 }
 
 impl std::convert::TryFrom<&rusqlite::Row<'_>> for Activity {
@@ -27,12 +118,17 @@ impl std::convert::TryFrom<&rusqlite::Row<'_>> for Activity {
             assigned: value.get::<usize, u32>(3).unwrap(),
             priority: 1.,
             children: vec![],
+            archived: value.get::<usize, bool>(4).unwrap(),
+            created_at: value.get::<usize, u64>(5).unwrap(),
+            updated_at: value.get::<usize, u64>(6).unwrap(),
+            last_session_at: value.get::<usize, Option<u64>>(7).unwrap(),
         })
     }
 }
 
 impl Activity {
-    const SELECT_QUERY: &str = "SELECT id, text, parent, assigned FROM activities";
+    const SELECT_QUERY: &str = "SELECT id, text, parent, assigned, archived, created_at, \
+        updated_at, last_session_at FROM activities";
 
     /// Iterates over a vector of activities recursively and applies a closure to each of them.
     pub fn activity_walker_dfs<F>(conn: &Conn, activities: &mut Vec<Activity>, f: &mut F)
@@ -69,6 +165,7 @@ impl Activity {
     }
 
     pub fn set_parent(conn: &Conn, child: ActID, parent: Option<ActID>) {
+        let now = crate::utils::current_unix().as_secs();
         let statement = match parent {
             Some(parent) => {
                 if parent == child {
@@ -79,19 +176,25 @@ impl Activity {
                     return;
                 }
 
+                // A moved activity goes after its new siblings.
                 format!(
-                    "UPDATE activities SET parent = '{}' WHERE id = '{}'",
-                    parent, child
+                    "UPDATE activities SET parent = '{0}', updated_at = {1}, position =
+                        (SELECT IFNULL(MAX(position) + 1, 0) FROM activities WHERE parent = '{0}')
+                        WHERE id = '{2}' AND parent IS NOT '{0}'",
+                    parent, now, child
                 )
             }
-            None => format!("UPDATE activities SET parent = NULL WHERE id = '{}'", child),
+            None => format!(
+                "UPDATE activities SET parent = NULL, updated_at = {}, position =
+                    (SELECT IFNULL(MAX(position) + 1, 0) FROM activities WHERE parent IS NULL)
+                    WHERE id = '{}' AND parent IS NOT NULL",
+                now, child
+            ),
         };
 
         sql::execute(conn, &statement).unwrap();
     }
 
-
-
     pub fn get_true_assigned(conn: &Conn, mut id: ActID) -> f32 {
         let assigned = Activity::fetch_activity(conn, id).unwrap().assigned as f32;
         let mut multiply = 1.;
@@ -104,37 +207,258 @@ impl Activity {
         assigned * multiply
     }
 
-    /// Queries children, but not recursively.
+    /// The names of the activity and all its ancestors, from the root down, joined by '/'.
+    pub fn path(conn: &Conn, id: ActID) -> String {
+        let mut names = vec![Activity::fetch_activity(conn, id).unwrap().text];
+        let mut id = id;
+
+        while let Some(parent) = Activity::get_parent(conn, id) {
+            names.push(parent.text);
+            id = parent.id;
+        }
+
+        names.reverse();
+        names.join("/")
+    }
+
+    /// Finds the activity at `path` below `parent` by the names along the way. Missing
+    /// activities are created if `created` is given, which is incremented for each of them.
+    pub fn resolve_path(
+        conn: &Conn,
+        parent: Option<ActID>,
+        path: &[String],
+        mut created: Option<&mut usize>,
+    ) -> Option<ActID> {
+        let mut parent = parent;
+
+        for name in path {
+            let existing = Activity::fetch_children(conn, parent)
+                .into_iter()
+                .find(|kid| &kid.text == name);
+
+            parent = match existing {
+                Some(kid) => Some(kid.id),
+                None => {
+                    let created = created.as_deref_mut()?;
+                    let activity = Activity::new(name.clone(), parent);
+                    sql::new_activity(conn, &activity).unwrap();
+                    *created += 1;
+                    Some(activity.id)
+                }
+            };
+        }
+
+        parent
+    }
+
+    /// Splits a path like `Music/Piano` into the names along it.
+    pub fn split_path(path: &str) -> Vec<String> {
+        path.split('/')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
+    /// Finds an activity by its path from the root, like `Music/Piano`, or by its id.
+    pub fn find(conn: &Conn, path: &str) -> Result<ActID, String> {
+        if let Ok(id) = Uuid::parse_str(path.trim()) {
+            if Activity::fetch_activity(conn, id).is_ok() {
+                return Ok(id);
+            }
+        }
+
+        let names = Self::split_path(path);
+        if names.is_empty() {
+            return Err("No activity given".to_string());
+        }
+        Self::resolve_path(conn, None, &names, None).ok_or(format!("No activity at '{}'", path))
+    }
+
+    /// Like [`Activity::find`], but also finds archived activities and their descendants.
+    pub fn find_with_archived(conn: &Conn, path: &str) -> Result<ActID, String> {
+        if let Ok(id) = Uuid::parse_str(path.trim()) {
+            if Activity::fetch_activity(conn, id).is_ok() {
+                return Ok(id);
+            }
+        }
+
+        let names = Self::split_path(path);
+        if names.is_empty() {
+            return Err("No activity given".to_string());
+        }
+        let mut parent = None;
+        for name in &names {
+            parent = Activity::fetch_all_children(conn, parent)
+                .into_iter()
+                .find(|kid| &kid.text == name)
+                .map(|kid| Some(kid.id))
+                .ok_or(format!("No activity at '{}'", path))?;
+        }
+        Ok(parent.unwrap())
+    }
+
+    /// The id of the activity along with the ids of all its descendants, archived or not.
+    pub fn subtree_ids(conn: &Conn, id: ActID) -> Vec<ActID> {
+        let mut ids = vec![id];
+        let mut idx = 0;
+
+        while idx < ids.len() {
+            let kids = Activity::fetch_all_children(conn, Some(ids[idx]));
+            ids.extend(kids.into_iter().map(|kid| kid.id));
+            idx += 1;
+        }
+
+        ids
+    }
+
+    /// Whether `id` is `root` or one of its descendants.
+    pub fn is_in_subtree(conn: &Conn, id: ActID, root: ActID) -> bool {
+        let mut next = Some(id);
+        while let Some(id) = next {
+            if id == root {
+                return true;
+            }
+            next = Activity::get_parent_index(conn, id);
+        }
+        false
+    }
+
+    /// The activities whose name contains the filter, ignoring case, along with their
+    /// ancestors so that they can be shown in the tree. Archived ones are left out.
+    pub fn matching(conn: &Conn, filter: &str) -> HashSet<ActID> {
+        let filter = filter.to_lowercase();
+        let mut ids = HashSet::new();
+        for act in Self::fetch_all_activities_flat(conn) {
+            if act.archived || !act.text.to_lowercase().contains(&filter) {
+                continue;
+            }
+            let mut next = Some(act.id);
+            while let Some(id) = next {
+                if !ids.insert(id) {
+                    break;
+                }
+                next = Self::get_parent_index(conn, id);
+            }
+        }
+        ids
+    }
+
+    /// Queries children, but not recursively. Archived ones are left out.
     pub fn fetch_children(conn: &Conn, parent: Option<ActID>) -> Vec<Activity> {
+        Self::fetch_all_children(conn, parent)
+            .into_iter()
+            .filter(|kid| !kid.archived)
+            .collect()
+    }
+
+    /// Queries children including the archived ones.
+    pub fn fetch_all_children(conn: &Conn, parent: Option<ActID>) -> Vec<Activity> {
         sql::query_map(conn, &Self::query_children(parent), |row| {
             Activity::try_from(row)
         })
         .unwrap()
     }
 
+    /// The archived activities, not including the descendants that are hidden with them.
+    pub fn fetch_archived(conn: &Conn) -> Vec<Activity> {
+        let statement = format!("{} WHERE archived = 1", Self::SELECT_QUERY);
+        sql::query_map(conn, &statement, |row| Activity::try_from(row)).unwrap()
+    }
+
+    pub fn archive(conn: &Conn, id: ActID) {
+        sql::set_archived(conn, id, true);
+    }
+
+    /// Restores the activity, and its ancestors if they're archived, so that it shows up again.
+    pub fn restore(conn: &Conn, id: ActID) {
+        let mut next = Some(id);
+        while let Some(id) = next {
+            sql::set_archived(conn, id, false);
+            next = Activity::get_parent_index(conn, id);
+        }
+    }
+
+    /// Deletes the activity and its descendants for good, with their history and notes.
+    /// Their sessions stay counted in the ancestors, which did have that time spent on them.
+    pub fn purge(conn: &Conn, id: ActID) {
+        for id in Activity::subtree_ids(conn, id) {
+            sql::execute(conn, &format!("DELETE FROM history WHERE id = '{}'", id)).unwrap();
+            sql::execute(conn, &format!("DELETE FROM timer WHERE id = '{}'", id)).unwrap();
+            crate::notes::delete(conn, id);
+            sql::delete_activity(conn, id);
+        }
+    }
+
     fn query_children(parent: Option<ActID>) -> String {
         match parent {
-            Some(id) => format!("{} WHERE parent = '{}'", Self::SELECT_QUERY, id),
-            None => format!("{} WHERE parent IS NULL", Self::SELECT_QUERY),
+            Some(id) => format!(
+                "{} WHERE parent = '{}' ORDER BY position, rowid",
+                Self::SELECT_QUERY,
+                id
+            ),
+            None => format!(
+                "{} WHERE parent IS NULL ORDER BY position, rowid",
+                Self::SELECT_QUERY
+            ),
         }
     }
 
-    fn update_text(conn: &Conn, id: ActID, text: &String) -> Result<(), rusqlite::Error> {
-        let statement = format!(
-            "UPDATE activities SET text = \"{}\" WHERE id = '{}'",
-            text, id
-        );
+    /// Moves the activity among its siblings, archived ones included, and numbers them
+    /// from 0 again.
+    pub fn reorder(conn: &Conn, id: ActID, to: Reorder) {
+        let parent = Self::get_parent_index(conn, id);
+        let mut siblings: Vec<ActID> = Self::fetch_all_children(conn, parent)
+            .into_iter()
+            .map(|kid| kid.id)
+            .collect();
+        let idx = siblings.iter().position(|sibling| *sibling == id).unwrap();
+        let new_idx = match to {
+            Reorder::Up => idx.saturating_sub(1),
+            Reorder::Down => (idx + 1).min(siblings.len() - 1),
+            Reorder::Top => 0,
+            Reorder::Bottom => siblings.len() - 1,
+        };
+        siblings.remove(idx);
+        siblings.insert(new_idx, id);
+
+        for (position, sibling) in siblings.into_iter().enumerate() {
+            sql::set_position(conn, sibling, position as u32);
+        }
+    }
 
-        sql::execute(conn, &statement)
+    /// Every activity, archived ones included, in the order of the tree: each one
+    /// followed by its descendants, siblings by their position.
+    pub fn fetch_all_in_tree_order(conn: &Conn) -> Vec<Activity> {
+        fn recursive(conn: &Conn, parent: Option<ActID>, activities: &mut Vec<Activity>) {
+            for kid in Activity::fetch_all_children(conn, parent) {
+                let id = kid.id;
+                activities.push(kid);
+                recursive(conn, Some(id), activities);
+            }
+        }
+
+        let mut activities = vec![];
+        recursive(conn, None, &mut activities);
+        activities
+    }
+
+    pub fn update_text(conn: &Conn, id: ActID, text: &String) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "UPDATE activities SET text = ?1, updated_at = ?2 WHERE id = ?3",
+            rusqlite::params![text, crate::utils::current_unix().as_secs(), id.to_string()],
+        )
+        .map(|_| ())
     }
 
-    pub fn modify_text(&mut self, text: String, conn: &Conn) {
-        Self::update_text(conn, self.id, &text).unwrap();
+    pub fn modify_text(&mut self, text: String, conn: &Conn) -> Result<(), rusqlite::Error> {
+        Self::update_text(conn, self.id, &text)?;
         self.text = text;
+        Ok(())
     }
 
     pub fn new(text: String, parent: Option<ActID>) -> Self {
         let id = Uuid::new_v4();
+        let now = crate::utils::current_unix().as_secs();
         Self {
             id,
             text,
@@ -142,44 +466,70 @@ impl Activity {
             assigned: 50,
             parent,
             children: vec![],
+            archived: false,
+            created_at: now,
+            updated_at: now,
+            last_session_at: None,
         }
     }
 
     fn format_duration(duration: std::time::Duration) -> String {
-    let seconds = duration.as_secs();
-    let days = seconds / 86400;
-    let hours = (seconds % 86400) / 3600;
-    let minutes = (seconds % 3600) / 60;
-    let seconds = seconds % 60;
+        let seconds = duration.as_secs();
+        let days = seconds / 86400;
+        let hours = (seconds % 86400) / 3600;
+        let minutes = (seconds % 3600) / 60;
+        let seconds = seconds % 60;
+
+        let mut result = String::new();
+        if days > 0 {
+            result.push_str(&format!("{}d ", days));
+        }
+        if hours > 0 {
+            result.push_str(&format!("{}h ", hours));
+        }
+        if minutes > 0 {
+            result.push_str(&format!("{}m ", minutes));
+        }
+        if seconds > 0 || result.is_empty() {
+            result.push_str(&format!("{}s", seconds));
+        }
 
-    let mut result = String::new();
-    if days > 0 {
-        result.push_str(&format!("{}d ", days));
-    }
-    if hours > 0 {
-        result.push_str(&format!("{}h ", hours));
-    }
-    if minutes > 0 {
-        result.push_str(&format!("{}m ", minutes));
-    }
-    if seconds > 0 || result.is_empty() {
-        result.push_str(&format!("{}s", seconds));
+        result
     }
 
-    result
-}
-
     pub fn display_flat(&self, conn: &Conn) -> String {
         format!(
             "{}:  {:.1}, {}/day",
             self.text,
             Self::calculate_priority(conn, self.id).powf(0.5),
-            Self::format_duration(crate::history::Session::average_daily_weighted_time_spent_from_activity(conn, self.id)))}
-                                  
-    
+            Self::format_duration(
+                crate::history::Session::average_daily_weighted_time_spent_from_activity(
+                    conn, self.id
+                )
+            )
+        )
+    }
+
+    /// When the activity was added, last changed and last had a session, in UTC.
+    pub fn display_timestamps(&self) -> String {
+        let format = |secs: u64| {
+            let time = crate::utils::UtcDateTime::from_unix(secs);
+            format!("{} {}", time.date(), &time.time()[..5])
+        };
+        let last_session = match self.last_session_at {
+            Some(secs) => format(secs),
+            None => "never".to_string(),
+        };
+        format!(
+            "Created {}, changed {}, last session {}",
+            format(self.created_at),
+            format(self.updated_at),
+            last_session
+        )
+    }
 
     pub fn calculate_priority(conn: &Conn, id: ActID) -> f32 {
-        let total = std::time::Duration::from_secs(86400 / 2);
+        let total = std::time::Duration::from_secs_f32(crate::config::get().budget_hours * 3600.);
         let time_spent = crate::history::Session::total_weighted_time_spent_from_activity(conn, id);
 
         let ratio = (time_spent.as_secs_f32() / 60. + 1.) / (total.as_secs_f32() / 60. + 1.);
@@ -206,21 +556,124 @@ impl Activity {
         .unwrap()
     }
 
+    /// How many descendants, sessions and notes deleting the activity would affect.
+    pub fn delete_impact(conn: &Conn, id: ActID) -> DeleteImpact {
+        let ids = Self::subtree_ids(conn, id);
+        DeleteImpact {
+            descendants: ids.len() - 1,
+            sessions: Session::get_history(conn, id).len(),
+            direct_sessions: Session::fetch_direct_sessions_of(conn, id).len(),
+            notes: ids
+                .iter()
+                .filter(|id| crate::notes::read(conn, **id).is_some())
+                .count(),
+            has_note: crate::notes::read(conn, id).is_some(),
+        }
+    }
+
+    pub fn delete(conn: &Conn, id: ActID, mode: DeleteMode) {
+        match mode {
+            DeleteMode::Lift { merge_sessions } => {
+                if !merge_sessions {
+                    Session::delete_sessions(conn, id, false);
+                }
+                Self::delete_activity(conn, id);
+            }
+            DeleteMode::Subtree => {
+                Session::delete_sessions(conn, id, true);
+                for id in Self::subtree_ids(conn, id) {
+                    sql::execute(conn, &format!("DELETE FROM timer WHERE id = '{}'", id)).unwrap();
+                    crate::notes::delete(conn, id);
+                    sql::delete_activity(conn, id);
+                }
+            }
+        }
+    }
+
+    /// Deletes the activity and moves its children up to its parent. The sessions logged
+    /// on it stay counted for the ancestors, where they now count as the parent's.
     pub fn delete_activity(conn: &Conn, id: ActID) {
         // Before deleting activity, make sure all the children get the parent of the to-be-deleted
         // activity.
         let activity = Self::fetch_activity(conn, id).unwrap();
         let parent = activity.parent;
 
-        let children = Self::fetch_children(conn, Some(id));
+        let children = Self::fetch_all_children(conn, Some(id));
 
         for child in children {
             Self::set_parent(conn, child.id, parent);
         }
 
+        sql::execute(conn, &format!("DELETE FROM history WHERE id = '{}'", id)).unwrap();
+        sql::execute(conn, &format!("DELETE FROM timer WHERE id = '{}'", id)).unwrap();
+        crate::notes::delete(conn, id);
         sql::delete_activity(conn, id);
     }
 
+    /// The leaves, i.e. the activities you can actually spend time on, ordered from
+    /// highest to lowest priority. Archived activities and their descendants aren't ranked.
+    pub fn fetch_by_priority(conn: &Conn) -> Vec<Activity> {
+        fn recursive(conn: &Conn, parent: Option<ActID>, leaves: &mut Vec<Activity>) {
+            for kid in Activity::fetch_children(conn, parent) {
+                let id = kid.id;
+                let before = leaves.len();
+                recursive(conn, Some(id), leaves);
+                if leaves.len() == before {
+                    leaves.push(kid);
+                }
+            }
+        }
+
+        let mut leaves = vec![];
+        recursive(conn, None, &mut leaves);
+        Self::assign_priorities(conn, &mut leaves);
+
+        leaves.sort_by_key(|leaf| std::cmp::Reverse((leaf.priority * 1000.) as u64));
+        leaves
+    }
+
+    /// The leaves like [`Activity::fetch_by_priority`], ordered by `key`. Ties keep
+    /// their priority order.
+    pub fn fetch_sorted(conn: &Conn, key: SortKey) -> Vec<Activity> {
+        let mut leaves = Self::fetch_by_priority(conn);
+        match key {
+            SortKey::Priority => {}
+            SortKey::Created => leaves.sort_by_key(|leaf| std::cmp::Reverse(leaf.created_at)),
+            SortKey::Updated => leaves.sort_by_key(|leaf| std::cmp::Reverse(leaf.updated_at)),
+            SortKey::LastSession => {
+                leaves.sort_by_key(|leaf| std::cmp::Reverse(leaf.last_session_at))
+            }
+        }
+        leaves
+    }
+
+    /// Sets the assigned value of an activity, and scales its siblings so that they
+    /// share the rest.
+    pub fn assign(conn: &Conn, id: ActID, assigned: u32) {
+        let assigned = assigned.min(100);
+        let parent = Self::get_parent_index(conn, id);
+        let siblings: Vec<Activity> = Self::fetch_children(conn, parent)
+            .into_iter()
+            .filter(|act| act.id != id)
+            .collect();
+
+        if siblings.is_empty() {
+            sql::set_assigned(conn, id, 100);
+            return;
+        }
+
+        let nums = siblings
+            .iter()
+            .map(|act| act.assigned.max(1) as i32)
+            .collect();
+        let normalized = crate::utils::normalize_vec(nums, 100 - assigned as i32);
+
+        sql::set_assigned(conn, id, assigned);
+        for (sibling, assigned) in siblings.iter().zip(normalized) {
+            sql::set_assigned(conn, sibling.id, assigned.max(0) as u32);
+        }
+    }
+
     pub fn normalize_assignments(conn: &Conn) {
         fn recursive(conn: &Conn, parent: Option<ActID>) {
             let kids = Activity::fetch_children(conn, parent);
@@ -237,3 +690,193 @@ impl Activity {
         recursive(conn, None);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_restore_purge() {
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        let piano = Activity::new("Piano".to_string(), Some(music.id));
+        let guitar = Activity::new("Guitar".to_string(), Some(music.id));
+        for act in [&music, &piano, &guitar] {
+            sql::new_activity(&conn, act).unwrap();
+        }
+        Session::log(&conn, piano.id, 30., 1000);
+        crate::notes::write(&conn, piano.id, "Scales");
+
+        let ranked = |conn: &Conn| -> Vec<ActID> {
+            Activity::fetch_by_priority(conn)
+                .into_iter()
+                .map(|act| act.id)
+                .collect()
+        };
+
+        Activity::archive(&conn, piano.id);
+        Activity::normalize_assignments(&conn);
+        assert_eq!(ranked(&conn), vec![guitar.id]);
+        assert_eq!(
+            Activity::fetch_activity(&conn, guitar.id).unwrap().assigned,
+            100
+        );
+        assert_eq!(Session::get_history(&conn, piano.id).len(), 1);
+
+        Activity::archive(&conn, guitar.id);
+        assert_eq!(ranked(&conn), vec![music.id]);
+
+        Activity::archive(&conn, music.id);
+        assert!(ranked(&conn).is_empty());
+        Activity::restore(&conn, piano.id);
+        assert_eq!(ranked(&conn), vec![piano.id]);
+        assert_eq!(Activity::fetch_archived(&conn).len(), 1);
+
+        Activity::purge(&conn, piano.id);
+        assert!(Activity::fetch_activity(&conn, piano.id).is_err());
+        assert!(crate::notes::read(&conn, piano.id).is_none());
+        assert_eq!(Session::get_history(&conn, music.id).len(), 1);
+    }
+
+    #[test]
+    fn test_delete_modes() {
+        let setup = || {
+            let conn = sql::init_in_memory();
+            let music = Activity::new("Music".to_string(), None);
+            let piano = Activity::new("Piano".to_string(), Some(music.id));
+            let scales = Activity::new("Scales".to_string(), Some(piano.id));
+            for act in [&music, &piano, &scales] {
+                sql::new_activity(&conn, act).unwrap();
+            }
+            Session::log(&conn, piano.id, 10., 1000);
+            Session::log(&conn, scales.id, 20., 2000);
+            (conn, music.id, piano.id, scales.id)
+        };
+        let minutes = |conn: &Conn, id: ActID| -> Vec<f64> {
+            Session::get_history(conn, id)
+                .iter()
+                .map(|session| session.minutes().round())
+                .collect()
+        };
+
+        let (conn, music, piano, scales) = setup();
+        let impact = Activity::delete_impact(&conn, piano);
+        assert_eq!(
+            (impact.descendants, impact.sessions, impact.direct_sessions),
+            (1, 2, 1)
+        );
+        Activity::delete(
+            &conn,
+            piano,
+            DeleteMode::Lift {
+                merge_sessions: true,
+            },
+        );
+        assert_eq!(Activity::get_parent_index(&conn, scales), Some(music));
+        assert_eq!(minutes(&conn, music), vec![10., 20.]);
+        assert_eq!(Session::fetch_direct_sessions_of(&conn, music).len(), 1);
+
+        let (conn, music, piano, _) = setup();
+        Activity::delete(
+            &conn,
+            piano,
+            DeleteMode::Lift {
+                merge_sessions: false,
+            },
+        );
+        assert_eq!(minutes(&conn, music), vec![20.]);
+
+        let (conn, music, piano, scales) = setup();
+        Activity::delete(&conn, piano, DeleteMode::Subtree);
+        assert!(Activity::fetch_activity(&conn, scales).is_err());
+        assert!(minutes(&conn, music).is_empty());
+        assert!(Session::fetch_direct_sessions(&conn).is_empty());
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        let piano = Activity::new("Piano".to_string(), Some(music.id));
+        for act in [&music, &piano] {
+            sql::new_activity(&conn, act).unwrap();
+        }
+        let fetch = |id| Activity::fetch_activity(&conn, id).unwrap();
+        assert!(fetch(piano.id).created_at > 0);
+        assert_eq!(fetch(piano.id).last_session_at, None);
+
+        Session::log(&conn, piano.id, 10., 1000);
+        Session::log(&conn, piano.id, 10., 3000);
+        Session::log(&conn, music.id, 10., 2000);
+        assert_eq!(fetch(piano.id).last_session_at, Some(3000));
+        assert_eq!(fetch(music.id).last_session_at, Some(3000));
+        Session::unlog(&conn, piano.id, 10., 3000);
+        assert_eq!(fetch(music.id).last_session_at, Some(2000));
+
+        let reset = || sql::execute(&conn, "UPDATE activities SET updated_at = 0").unwrap();
+        reset();
+        sql::set_assigned(&conn, piano.id, fetch(piano.id).assigned);
+        assert_eq!(fetch(piano.id).updated_at, 0);
+        sql::set_assigned(&conn, piano.id, 30);
+        assert!(fetch(piano.id).updated_at > 0);
+        reset();
+        Activity::update_text(&conn, music.id, &"\"Sound\" & 'noise'".to_string()).unwrap();
+        Activity::set_parent(&conn, piano.id, None);
+        assert!(fetch(piano.id).updated_at > 0 && fetch(music.id).updated_at > 0);
+        assert_eq!(fetch(music.id).text, "\"Sound\" & 'noise'");
+    }
+
+    #[test]
+    fn test_reorder() {
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        sql::new_activity(&conn, &music).unwrap();
+        let mut kids = vec![];
+        for name in ["Piano", "Guitar", "Drums"] {
+            let kid = Activity::new(name.to_string(), Some(music.id));
+            sql::new_activity(&conn, &kid).unwrap();
+            kids.push(kid.id);
+        }
+        let names = |parent| -> Vec<String> {
+            Activity::fetch_children(&conn, parent)
+                .into_iter()
+                .map(|kid| kid.text)
+                .collect()
+        };
+        assert_eq!(names(Some(music.id)), ["Piano", "Guitar", "Drums"]);
+
+        Activity::reorder(&conn, kids[2], Reorder::Top);
+        Activity::reorder(&conn, kids[1], Reorder::Up);
+        assert_eq!(names(Some(music.id)), ["Drums", "Guitar", "Piano"]);
+        Activity::reorder(&conn, kids[2], Reorder::Bottom);
+        Activity::reorder(&conn, kids[2], Reorder::Down);
+        assert_eq!(names(Some(music.id)), ["Guitar", "Piano", "Drums"]);
+
+        // Moving to another parent puts it last there.
+        Activity::set_parent(&conn, kids[0], None);
+        assert_eq!(names(None), ["Music", "Piano"]);
+        Activity::set_parent(&conn, kids[0], Some(music.id));
+        assert_eq!(names(Some(music.id)), ["Guitar", "Drums", "Piano"]);
+    }
+
+    #[test]
+    fn test_matching() {
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        let piano = Activity::new("Piano".to_string(), Some(music.id));
+        let scales = Activity::new("Scales".to_string(), Some(piano.id));
+        let sports = Activity::new("Sports".to_string(), None);
+        for act in [&music, &piano, &scales, &sports] {
+            sql::new_activity(&conn, act).unwrap();
+        }
+
+        let matching = Activity::matching(&conn, "sCAL");
+        assert_eq!(matching, HashSet::from([music.id, piano.id, scales.id]));
+        assert_eq!(Activity::matching(&conn, "").len(), 4);
+
+        sql::set_collapsed(&conn, piano.id, true);
+        assert!(sql::fetch_collapsed(&conn).contains(&piano.id));
+        sql::delete_activity(&conn, piano.id);
+        assert!(sql::fetch_collapsed(&conn).is_empty());
+    }
+}
@@ -0,0 +1,703 @@
+//! The command line interface. Running acts without a subcommand opens the GUI.
+
+use clap::{Parser, Subcommand};
+
+use crate::activity::{Activity, DeleteMode};
+use crate::export::Filter;
+use crate::history::Session;
+use crate::report::{Ranked, SessionReport, SlotReport, Stats, TimerReport, TreeNode};
+use crate::slots::Slot;
+use crate::timer::Timer;
+use crate::utils::UtcDateTime;
+use crate::ActID;
+use crate::Conn;
+
+/// Activities are addressed by their path from the root, like `Music/Piano`, or by id.
+#[derive(Parser, Debug)]
+#[command(name = "acts", about = "Decide what to spend your time on")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Print the output of read-only commands as JSON. See the `report` module for its shape.
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Use this database instead of the configured one.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub db: Option<String>,
+    /// Use this notes directory instead of the configured one.
+    #[arg(long, global = true, value_name = "DIR")]
+    pub notes_dir: Option<String>,
+    /// Use this profile instead of the configured one.
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Override a setting of the config file, like `--set decay=0.98`. Can be repeated.
+    #[arg(long, global = true, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+}
+
+impl Cli {
+    /// The settings given on the command line, which override the config file.
+    pub fn overrides(&self) -> Result<crate::config::Layer, String> {
+        let mut layer = crate::config::Layer::default();
+        for setting in &self.set {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or(format!("Expected KEY=VALUE: {}", setting))?;
+            layer.set(key.trim(), value)?;
+        }
+        if let Some(db) = &self.db {
+            layer.set("db_path", db)?;
+        }
+        if let Some(notes_dir) = &self.notes_dir {
+            layer.set("notes_dir", notes_dir)?;
+        }
+        if let Some(profile) = &self.profile {
+            layer.set("profile", profile)?;
+        }
+        Ok(layer)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Add an activity, creating any missing parents along the path.
+    Add { path: String },
+    /// Log a session on an activity.
+    Log {
+        path: String,
+        /// In minutes, or like `1h30m`.
+        duration: String,
+        /// When the session ended, as `YYYY-MM-DD[THH:MM[:SS]]` in UTC. Defaults to now.
+        #[arg(long)]
+        at: Option<String>,
+    },
+    /// Print the activity tree with assigned percentages.
+    Tree,
+    /// Print the leaf activities from highest to lowest priority.
+    List {
+        /// Order by when they were added, last changed or last had a session instead,
+        /// most recent first.
+        #[arg(long, value_enum, default_value = "priority")]
+        sort: crate::activity::SortKey,
+    },
+    /// Print statistics about an activity.
+    Stats { path: String },
+    /// Print logged sessions, optionally only those of a subtree.
+    Sessions {
+        path: Option<String>,
+        /// Only sessions logged on or after this date, as `YYYY-MM-DD`.
+        #[arg(long)]
+        from: Option<String>,
+        /// Only sessions logged on or before this date, as `YYYY-MM-DD`.
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Delete an activity. Its children are moved up to its parent, and the sessions
+    /// logged on it count as its parent's.
+    Rm {
+        path: String,
+        /// Delete the children too, with all the sessions in the subtree.
+        #[arg(long)]
+        subtree: bool,
+        /// Delete the sessions logged on the activity instead of keeping them for its parent.
+        #[arg(long, conflicts_with = "subtree")]
+        drop_sessions: bool,
+    },
+    /// Archive an activity, hiding it with its children from the tree and the ranking.
+    Archive { path: String },
+    /// Print the archived activities.
+    Archived,
+    /// Bring back an archived activity, and its archived parents.
+    Restore { path: String },
+    /// Delete an activity with its children, history and notes for good.
+    Purge { path: String },
+    /// Move an activity below another one, or to the root with `/`.
+    Mv { path: String, parent: String },
+    /// Move an activity among its siblings, which keep the order they're given.
+    Reorder {
+        path: String,
+        #[arg(value_enum)]
+        to: crate::activity::Reorder,
+    },
+    /// Merge an activity into another one: its sessions, children and note move to the
+    /// target and it's deleted.
+    Merge {
+        source: String,
+        target: String,
+        /// Print what would change without merging.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Split an activity into new children, like `acts split Music Piano=60 Theory=40`,
+    /// handing them the sessions logged on it.
+    Split {
+        path: String,
+        /// The children as `Name=assigned`. Those without a value share the rest of 100.
+        #[arg(required = true)]
+        parts: Vec<String>,
+        /// The % of each session every part gets, like `50,50`. Defaults to the assigned values.
+        #[arg(long, conflicts_with = "each")]
+        percent: Option<String>,
+        /// The part each session goes to, or `-` to keep it, like `Piano,-,Theory`. In the
+        /// order `acts sessions` prints the sessions logged on the activity itself.
+        #[arg(long)]
+        each: Option<String>,
+    },
+    /// Start timing an activity, stopping the timer that's running.
+    Start { path: String },
+    /// Stop the timer and log the time since it was started.
+    Stop,
+    /// Print the running timer.
+    Timer,
+    /// Print the running timer or the top priority for a desktop bar. See the `status`
+    /// module for how to set it up.
+    Status {
+        #[arg(long, value_enum, default_value = "text")]
+        format: crate::status::Format,
+        /// Stop the running timer, or start the top priority, before printing.
+        #[arg(long)]
+        toggle: bool,
+    },
+    /// Search activity names and notes.
+    Search { query: Vec<String> },
+    /// Edit an activity's note in $VISUAL or $EDITOR.
+    Note {
+        path: String,
+        /// Print the note instead of editing it.
+        #[arg(long)]
+        print: bool,
+    },
+    /// Open the terminal UI.
+    Tui,
+    /// Serve a JSON API over HTTP. See the `server` module for the routes.
+    Serve {
+        /// The address to listen on. Use `0.0.0.0:7070` to make it reachable on the LAN.
+        #[arg(long, default_value = "127.0.0.1:7070")]
+        addr: String,
+    },
+    /// Set an activity's assigned percentage, scaling its siblings to share the rest.
+    Assign { path: String, percent: u32 },
+    /// Undo the last change to the activities, assignments or sessions.
+    Undo,
+    /// Redo the last undone change.
+    Redo,
+    /// Print the changes that can be undone and redone, the most recent first.
+    Journal,
+    /// List and manage profiles, each with its own activities. See the `profile` module.
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+    /// Plan time for activities, which the iCalendar export includes.
+    Plan {
+        #[command(subcommand)]
+        command: PlanCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PlanCommand {
+    /// Plan a slot for an activity.
+    Add {
+        path: String,
+        /// When the slot starts, as `YYYY-MM-DDTHH:MM[:SS]` in UTC.
+        start: String,
+        /// In minutes, or like `1h30m`.
+        duration: String,
+        /// What the time is for.
+        #[arg(long, default_value = "")]
+        title: String,
+    },
+    /// Print the planned slots, optionally only those of a subtree.
+    List { path: Option<String> },
+    /// Remove a slot by the id `acts plan list` prints.
+    Remove { id: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    /// Print the profiles, marking the active one.
+    List,
+    Create {
+        name: String,
+    },
+    Rename {
+        old: String,
+        new: String,
+    },
+    /// Delete a profile with its database and notes.
+    Delete {
+        name: String,
+    },
+}
+
+/// Runs the command, returning the process' exit code.
+pub fn run(conn: &Conn, command: Command, json: bool) -> i32 {
+    // The status is polled every second, so it shouldn't write on every call.
+    let polled = matches!(command, Command::Status { toggle: false, .. });
+    if !polled {
+        Activity::normalize_assignments(conn);
+        crate::vault::sync(conn);
+    }
+    let result = match label(&command) {
+        Some(label) => crate::journal::record(conn, &label, || execute(conn, command, json)),
+        None => execute(conn, command, json),
+    };
+    if !polled {
+        Activity::normalize_assignments(conn);
+        crate::vault::sync(conn);
+    }
+
+    match result {
+        Ok(()) => 0,
+        Err(e) if json => {
+            eprintln!("{}", serde_json::json!({ "error": e }));
+            1
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+/// What the command is called in the journal, if it changes anything that can be undone.
+fn label(command: &Command) -> Option<String> {
+    let label = match command {
+        Command::Add { path } => format!("Add {}", path),
+        Command::Log { path, duration, .. } => format!("Log {} on {}", duration, path),
+        Command::Rm { path, .. } => format!("Delete {}", path),
+        Command::Archive { path } => format!("Archive {}", path),
+        Command::Restore { path } => format!("Restore {}", path),
+        Command::Mv { path, parent } => format!("Move {} to {}", path, parent),
+        Command::Reorder { path, to } => format!("Move {} {}", path, to.label()),
+        Command::Merge {
+            source,
+            target,
+            dry_run: false,
+        } => format!("Merge {} into {}", source, target),
+        Command::Split { path, parts, .. } => format!("Split {} into {}", path, parts.join(", ")),
+        Command::Start { path } => format!("Start {}", path),
+        Command::Stop => "Stop the timer".to_string(),
+        Command::Status { toggle: true, .. } => "Toggle the timer".to_string(),
+        Command::Assign { path, percent } => format!("Assign {}% to {}", percent, path),
+        _ => return None,
+    };
+    Some(label)
+}
+
+fn print_json<T: serde::Serialize>(value: &T) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+fn execute(conn: &Conn, command: Command, json: bool) -> Result<(), String> {
+    match command {
+        Command::Add { path } => {
+            let names = Activity::split_path(&path);
+            if names.is_empty() {
+                return Err("No activity given".to_string());
+            }
+            let mut created = 0;
+            Activity::resolve_path(conn, None, &names, Some(&mut created));
+            if created == 0 {
+                return Err(format!("'{}' already exists", path));
+            }
+        }
+        Command::Log { path, duration, at } => {
+            let id = Activity::find(conn, &path)?;
+            let minutes = crate::utils::parse_minutes(&duration)
+                .ok_or(format!("Invalid duration: {}", duration))?;
+            let timestamp = match &at {
+                Some(at) => {
+                    crate::utils::parse_datetime(at).ok_or(format!("Invalid time: {}", at))?
+                }
+                None => crate::utils::current_unix().as_secs(),
+            };
+            if timestamp > crate::utils::current_unix().as_secs() {
+                return Err(format!("Can't log time in the future: {}", at.unwrap()));
+            }
+            Session::log(conn, id, minutes, timestamp);
+        }
+        Command::Tree => {
+            let roots = TreeNode::roots(conn);
+            if json {
+                print_json(&roots);
+            } else {
+                print_tree(&roots, 0);
+            }
+        }
+        Command::List { sort } if json => print_json(&Ranked::sorted(conn, sort)),
+        Command::List { sort } => {
+            for activity in Activity::fetch_sorted(conn, sort) {
+                println!("{}", activity.display_flat(conn));
+            }
+        }
+        Command::Stats { path } => {
+            let stats = Stats::new(conn, Activity::find(conn, &path)?);
+            if json {
+                print_json(&stats);
+            } else {
+                print_stats(&stats);
+            }
+        }
+        Command::Sessions { path, from, to } => {
+            let date = |date: Option<String>| match date {
+                Some(date) => crate::utils::parse_date(&date)
+                    .map(Some)
+                    .ok_or(format!("Invalid date: {}", date)),
+                None => Ok(None),
+            };
+            let filter = Filter {
+                from: date(from)?,
+                to: date(to)?.map(|to| to + 86400),
+                subtree: path.map(|path| Activity::find(conn, &path)).transpose()?,
+            };
+
+            let sessions = SessionReport::all(conn, &filter);
+            if json {
+                print_json(&sessions);
+            } else {
+                for session in sessions {
+                    let end = UtcDateTime::from_unix(session.timestamp);
+                    println!(
+                        "{} {}  {:>7.1}m  {}",
+                        end.date(),
+                        end.time(),
+                        session.duration,
+                        session.path
+                    );
+                }
+            }
+        }
+        Command::Rm {
+            path,
+            subtree,
+            drop_sessions,
+        } => {
+            let id = Activity::find(conn, &path)?;
+            let impact = Activity::delete_impact(conn, id);
+            let children = Activity::fetch_all_children(conn, Some(id)).len();
+            let mode = if subtree {
+                DeleteMode::Subtree
+            } else {
+                DeleteMode::Lift {
+                    merge_sessions: !drop_sessions,
+                }
+            };
+            Activity::delete(conn, id, mode);
+
+            if json {
+                print_json(&impact);
+            } else if subtree {
+                println!(
+                    "Deleted {} activities, {} sessions and {} notes",
+                    impact.descendants + 1,
+                    impact.sessions,
+                    impact.notes
+                );
+            } else {
+                println!("Deleted {}, moved {} children up", path, children);
+            }
+        }
+        Command::Archive { path } => {
+            let id = Activity::find(conn, &path)?;
+            Activity::archive(conn, id);
+        }
+        Command::Archived => {
+            let archived: Vec<(ActID, String)> = Activity::fetch_archived(conn)
+                .into_iter()
+                .map(|act| (act.id, Activity::path(conn, act.id)))
+                .collect();
+            if json {
+                let entries: Vec<_> = archived
+                    .iter()
+                    .map(|(id, path)| serde_json::json!({ "id": id, "path": path }))
+                    .collect();
+                print_json(&entries);
+            } else {
+                for (_, path) in archived {
+                    println!("{}", path);
+                }
+            }
+        }
+        Command::Restore { path } => {
+            let id = Activity::find_with_archived(conn, &path)?;
+            Activity::restore(conn, id);
+        }
+        Command::Purge { path } => {
+            let id = Activity::find_with_archived(conn, &path)?;
+            Activity::purge(conn, id);
+        }
+        Command::Mv { path, parent } => {
+            let id = Activity::find(conn, &path)?;
+            let parent = match parent.trim() {
+                "/" => None,
+                parent => Some(Activity::find(conn, parent)?),
+            };
+            if let Some(parent) = parent {
+                if Activity::is_in_subtree(conn, parent, id) {
+                    return Err("Can't move an activity below itself".to_string());
+                }
+            }
+            Activity::set_parent(conn, id, parent);
+        }
+        Command::Reorder { path, to } => Activity::reorder(conn, Activity::find(conn, &path)?, to),
+        Command::Merge {
+            source,
+            target,
+            dry_run,
+        } => {
+            let source = Activity::find(conn, &source)?;
+            let target = Activity::find(conn, &target)?;
+            let preview = crate::merge::preview(conn, source, target)?;
+            if json {
+                print_json(&preview);
+            } else {
+                for change in &preview.totals {
+                    println!(
+                        "{}: {:.1}m -> {:.1}m",
+                        change.path, change.before, change.after
+                    );
+                }
+                if preview.ranking_before != preview.ranking_after {
+                    println!("Ranking:");
+                    for (idx, path) in preview.ranking_after.iter().enumerate() {
+                        let was = preview.ranking_before.iter().position(|p| p == path);
+                        match was {
+                            Some(was) if was != idx => {
+                                println!("  {}. {} (was {})", idx + 1, path, was + 1)
+                            }
+                            _ => println!("  {}. {}", idx + 1, path),
+                        }
+                    }
+                }
+            }
+            if !dry_run {
+                crate::merge::merge(conn, source, target)?;
+            }
+        }
+        Command::Split {
+            path,
+            parts,
+            percent,
+            each,
+        } => {
+            let id = Activity::find(conn, &path)?;
+            let parts = crate::split::parse_parts(&parts.join(","))?;
+            let distribution = match (percent, each) {
+                (Some(percent), _) => {
+                    crate::split::Distribution::Percent(crate::split::parse_percents(&percent)?)
+                }
+                (None, Some(each)) => {
+                    let owners = each
+                        .split(',')
+                        .map(str::trim)
+                        .map(|name| match name {
+                            "-" => Ok(None),
+                            name => parts
+                                .iter()
+                                .position(|part| part.name == name)
+                                .map(Some)
+                                .ok_or(format!("{} isn't one of the parts", name)),
+                        })
+                        .collect::<Result<_, String>>()?;
+                    crate::split::Distribution::Sessions(owners)
+                }
+                (None, None) => crate::split::Distribution::by_assigned(&parts),
+            };
+            for kid in crate::split::split(conn, id, &parts, &distribution)? {
+                let kid = Activity::fetch_activity(conn, kid).unwrap();
+                let minutes = Session::get_history(conn, kid.id)
+                    .iter()
+                    .fold(0., |total, session| total + session.minutes());
+                println!(
+                    "{} [{}%] {:.1}m",
+                    Activity::path(conn, kid.id),
+                    kid.assigned,
+                    minutes
+                );
+            }
+        }
+        Command::Start { path } => {
+            if let Some(session) = Timer::start(conn, Activity::find(conn, &path)?) {
+                println!(
+                    "Logged {:.1}m on {}",
+                    session.minutes(),
+                    Activity::path(conn, session.id)
+                );
+            }
+        }
+        Command::Stop => match Timer::stop(conn) {
+            Some(session) => {
+                println!(
+                    "Logged {:.1}m on {}",
+                    session.minutes(),
+                    Activity::path(conn, session.id)
+                )
+            }
+            None => return Err("No timer is running".to_string()),
+        },
+        Command::Timer => {
+            let timer = TimerReport::running(conn);
+            if json {
+                print_json(&timer);
+            } else if let Some(timer) = timer {
+                println!("{}  {:.1}m", timer.path, timer.elapsed);
+            }
+        }
+        Command::Status { format, toggle } => {
+            let clicked = format == crate::status::Format::I3blocks
+                && std::env::var("BLOCK_BUTTON").is_ok_and(|button| button == "1");
+            if toggle || clicked {
+                crate::status::toggle(conn);
+            }
+            crate::status::Status::new(conn).print(format);
+        }
+        Command::Search { query } => {
+            let hits = crate::search::search(conn, &query.join(" "));
+            if json {
+                print_json(&hits);
+            } else {
+                for hit in hits {
+                    println!("{}", hit.path);
+                    if !hit.snippet.is_empty() {
+                        println!("  {}", hit.snippet.replace('\n', " "));
+                    }
+                }
+            }
+        }
+        Command::Note { path, print } => {
+            let id = Activity::find(conn, &path)?;
+            let note = crate::notes::read(conn, id);
+            if !print {
+                crate::notes::edit_in_terminal(conn, id)?;
+            } else if json {
+                print_json(&serde_json::json!({ "id": id, "note": note }));
+            } else if let Some(note) = note {
+                print!("{}", note);
+            }
+        }
+        Command::Tui => crate::tui::run(conn).map_err(|e| e.to_string())?,
+        Command::Serve { addr } => crate::server::run(conn, &addr)?,
+        Command::Assign { path, percent } => {
+            if percent > 100 {
+                return Err("Percent can't be more than 100".to_string());
+            }
+            let id = Activity::find(conn, &path)?;
+            Activity::assign(conn, id, percent);
+        }
+        Command::Undo => match crate::journal::undo(conn) {
+            Some(label) => println!("Undid: {}", label),
+            None => return Err("Nothing to undo".to_string()),
+        },
+        Command::Redo => match crate::journal::redo(conn) {
+            Some(label) => println!("Redid: {}", label),
+            None => return Err("Nothing to redo".to_string()),
+        },
+        Command::Journal if json => print_json(&crate::journal::entries(conn)),
+        Command::Journal => {
+            for entry in crate::journal::entries(conn) {
+                let time = UtcDateTime::from_unix(entry.timestamp);
+                let undone = if entry.undone { " (undone)" } else { "" };
+                println!("{} {}  {}{}", time.date(), time.time(), entry.label, undone);
+            }
+        }
+        Command::Profile { command } => match command {
+            ProfileCommand::List if json => print_json(&crate::profile::list()),
+            ProfileCommand::List => {
+                for profile in crate::profile::list() {
+                    let marker = if profile.active { "*" } else { " " };
+                    println!("{} {}", marker, profile.name);
+                }
+            }
+            ProfileCommand::Create { name } => crate::profile::create(&name)?,
+            ProfileCommand::Rename { old, new } => crate::profile::rename(&old, &new)?,
+            ProfileCommand::Delete { name } => crate::profile::delete(&name)?,
+        },
+        Command::Plan { command } => match command {
+            PlanCommand::Add {
+                path,
+                start,
+                duration,
+                title,
+            } => {
+                let id = Activity::find(conn, &path)?;
+                let start = crate::utils::parse_datetime(&start)
+                    .ok_or(format!("Invalid time: {}", start))?;
+                let minutes = crate::utils::parse_minutes(&duration)
+                    .filter(|minutes| *minutes > 0.)
+                    .ok_or(format!("Invalid duration: {}", duration))?;
+                let length = std::time::Duration::from_secs_f64(minutes * 60.);
+                let slot = Slot::new(id, start, length, title);
+                slot.add(conn);
+                println!("{}", slot.id);
+            }
+            PlanCommand::List { path } => {
+                let filter = Filter {
+                    subtree: path.map(|path| Activity::find(conn, &path)).transpose()?,
+                    ..Filter::default()
+                };
+                let slots = SlotReport::all(conn, &filter);
+                if json {
+                    print_json(&slots);
+                } else {
+                    for slot in slots {
+                        let start = UtcDateTime::from_unix(slot.start);
+                        println!(
+                            "{}  {} {}  {:>7.1}m  {}  {}",
+                            slot.slot,
+                            start.date(),
+                            start.time(),
+                            slot.duration,
+                            slot.path,
+                            slot.title
+                        );
+                    }
+                }
+            }
+            PlanCommand::Remove { id } => {
+                let id =
+                    uuid::Uuid::parse_str(&id).map_err(|_| format!("Not a slot id: {}", id))?;
+                if !Slot::remove(conn, id) {
+                    return Err(format!("No slot with the id {}", id));
+                }
+            }
+        },
+    }
+    Ok(())
+}
+
+fn print_tree(nodes: &[TreeNode], depth: usize) {
+    for node in nodes {
+        println!(
+            "{}{} [{}%, {:.1}% overall]",
+            "  ".repeat(depth),
+            node.text,
+            node.assigned,
+            node.effective
+        );
+        print_tree(&node.children, depth + 1);
+    }
+}
+
+fn print_stats(stats: &Stats) {
+    println!("{}", stats.path);
+    println!("  id:             {}", stats.id);
+    println!(
+        "  assigned:       {}% ({:.1}% overall)",
+        stats.assigned, stats.effective
+    );
+    println!("  score:          {:.1}", stats.score);
+    println!("  sessions:       {}", stats.sessions);
+    println!("  total:          {:.1}m", stats.total_minutes);
+    println!("  weighted:       {:.1}m", stats.weighted_minutes);
+    println!("  daily weighted: {:.1}m", stats.daily_weighted_minutes);
+    if let Some(last) = stats.last_session {
+        let last = UtcDateTime::from_unix(last);
+        println!("  last session:   {} {}", last.date(), last.time());
+    }
+    let created = UtcDateTime::from_unix(stats.created_at);
+    println!("  created:        {} {}", created.date(), created.time());
+    let updated = UtcDateTime::from_unix(stats.updated_at);
+    println!("  changed:        {} {}", updated.date(), updated.time());
+}
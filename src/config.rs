@@ -0,0 +1,408 @@
+//! Settings, read from `$XDG_CONFIG_HOME/acts/config.toml` (`~/.config/acts/config.toml`).
+//!
+//! Every key is optional:
+//!
+//! ```toml
+//! db_path = "~/.local/share/acts/mydb.db"
+//! notes_dir = "~/.local/share/acts/notes"
+//! editor = "code --wait" # defaults to $VISUAL or $EDITOR
+//! terminal = "foot"       # runs terminal editors from the GUI, defaults to $TERMINAL
+//! theme = "dark"          # or "light"
+//! budget_hours = 12.0     # the daily time that priorities are calculated against
+//! decay = 0.99            # how much a session counts after a day, compounded daily
+//! profile = "default"     # see the `profile` module
+//! vault_dir = "~/vault/acts" # mirrors the notes as Markdown files, see the `vault` module
+//! ```
+//!
+//! Environment variables override the file, like `ACTS_DB_PATH` or `ACTS_DECAY`, and
+//! command line options (`acts --db <path>`, `acts --set decay=0.98`) override both.
+//!
+//! `db_path` and `notes_dir` are the default profile's. Other profiles keep theirs in
+//! their own directory, unless they're overridden from the environment or command line.
+
+use std::path::PathBuf;
+use std::sync::{RwLock, RwLockReadGuard};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// `None` is the profile's database in the data directory, see [`Config::db_path`].
+    pub db_path: Option<PathBuf>,
+    /// `None` is the profile's notes in the data directory, see [`Config::notes_dir`].
+    pub notes_dir: Option<PathBuf>,
+    pub editor: Option<String>,
+    pub terminal: Option<String>,
+    pub theme: Theme,
+    pub budget_hours: f32,
+    pub decay: f32,
+    pub profile: String,
+    pub vault_dir: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            db_path: None,
+            notes_dir: None,
+            editor: None,
+            terminal: None,
+            theme: Theme::Dark,
+            budget_hours: 12.,
+            decay: 0.99,
+            profile: crate::profile::DEFAULT.to_string(),
+            vault_dir: None,
+        }
+    }
+}
+
+impl Config {
+    /// The database to use. The data directory is only looked up, and created, here, so
+    /// the settings can be read without a home directory.
+    pub fn db_path(&self) -> PathBuf {
+        self.db_path
+            .clone()
+            .unwrap_or_else(|| self.data_dir().join("mydb.db"))
+    }
+
+    pub fn notes_dir(&self) -> PathBuf {
+        self.notes_dir
+            .clone()
+            .unwrap_or_else(|| self.data_dir().join("notes"))
+    }
+
+    fn data_dir(&self) -> PathBuf {
+        if self.profile == crate::profile::DEFAULT {
+            crate::sql::data_dir()
+        } else {
+            crate::profile::dir(&self.profile)
+        }
+    }
+}
+
+/// Some of the settings, like the contents of the config file or the overrides.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Layer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<Theme>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_hours: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decay: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_dir: Option<String>,
+}
+
+pub const KEYS: [&str; 9] = [
+    "db_path",
+    "notes_dir",
+    "editor",
+    "terminal",
+    "theme",
+    "budget_hours",
+    "decay",
+    "profile",
+    "vault_dir",
+];
+
+impl Layer {
+    /// Sets a key from its string form, as used by environment variables and `--set`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("Invalid value for {}: {}", key, value);
+        let value = value.trim();
+        match key {
+            "db_path" => self.db_path = Some(value.to_string()),
+            "notes_dir" => self.notes_dir = Some(value.to_string()),
+            "editor" => self.editor = Some(value.to_string()),
+            "terminal" => self.terminal = Some(value.to_string()),
+            "theme" => {
+                self.theme = Some(match value {
+                    "dark" => Theme::Dark,
+                    "light" => Theme::Light,
+                    _ => return Err(invalid()),
+                })
+            }
+            "budget_hours" => self.budget_hours = Some(value.parse().map_err(|_| invalid())?),
+            "decay" => self.decay = Some(value.parse().map_err(|_| invalid())?),
+            "profile" => self.profile = Some(value.to_string()),
+            "vault_dir" => self.vault_dir = Some(value.to_string()),
+            _ => return Err(format!("Unknown setting: {}", key)),
+        }
+        self.check()
+    }
+
+    /// Rejects values the priorities can't be calculated with.
+    pub fn check(&self) -> Result<(), String> {
+        if self
+            .budget_hours
+            .is_some_and(|hours| hours.is_nan() || hours <= 0.)
+        {
+            return Err("budget_hours has to be more than 0".to_string());
+        }
+        if self
+            .decay
+            .is_some_and(|decay| decay.is_nan() || decay <= 0. || decay > 1.)
+        {
+            return Err("decay has to be more than 0 and at most 1".to_string());
+        }
+        if let Some(profile) = &self.profile {
+            crate::profile::check_name(profile)?;
+        }
+        Ok(())
+    }
+
+    /// Parses a config file. Unknown keys and invalid values are left out, with a warning
+    /// for each, so the other settings still apply.
+    pub fn parse(s: &str) -> Result<(Self, Vec<String>), String> {
+        let table: toml::Table = s.parse().map_err(|e: toml::de::Error| e.to_string())?;
+
+        let mut layer = Self::default();
+        let mut warnings = vec![];
+        for (key, value) in table {
+            if !KEYS.contains(&key.as_str()) {
+                warnings.push(format!("Unknown setting in config file: {}", key));
+                continue;
+            }
+            let single = toml::Table::from_iter([(key.clone(), value)]);
+            let parsed = Self::deserialize(single)
+                .map_err(|e| e.message().to_string())
+                .and_then(|single| single.check().map(|_| single));
+            match parsed {
+                Ok(single) => layer.merge(single),
+                Err(e) => warnings.push(format!("Invalid value for {} in config file: {}", key, e)),
+            }
+        }
+        Ok((layer, warnings))
+    }
+
+    /// The overrides from `ACTS_*` environment variables.
+    fn from_env() -> (Self, Vec<String>) {
+        let mut layer = Self::default();
+        let mut warnings = vec![];
+        for key in KEYS {
+            if let Ok(value) = std::env::var(format!("ACTS_{}", key.to_uppercase())) {
+                if let Err(e) = layer.set(key, &value) {
+                    warnings.push(e);
+                }
+            }
+        }
+        (layer, warnings)
+    }
+
+    /// Takes the keys that are set in `other`.
+    fn merge(&mut self, other: Layer) {
+        self.db_path = other.db_path.or(self.db_path.take());
+        self.notes_dir = other.notes_dir.or(self.notes_dir.take());
+        self.editor = other.editor.or(self.editor.take());
+        self.terminal = other.terminal.or(self.terminal.take());
+        self.theme = other.theme.or(self.theme);
+        self.budget_hours = other.budget_hours.or(self.budget_hours);
+        self.decay = other.decay.or(self.decay);
+        self.profile = other.profile.or(self.profile.take());
+        self.vault_dir = other.vault_dir.or(self.vault_dir.take());
+    }
+
+    fn apply(&self, config: &mut Config) {
+        if let Some(db_path) = &self.db_path {
+            config.db_path = Some(expand_home(db_path));
+        }
+        if let Some(notes_dir) = &self.notes_dir {
+            config.notes_dir = Some(expand_home(notes_dir));
+        }
+        if let Some(editor) = &self.editor {
+            config.editor = Some(editor.clone());
+        }
+        if let Some(terminal) = &self.terminal {
+            config.terminal = Some(terminal.clone());
+        }
+        if let Some(theme) = self.theme {
+            config.theme = theme;
+        }
+        if let Some(budget_hours) = self.budget_hours {
+            config.budget_hours = budget_hours;
+        }
+        if let Some(decay) = self.decay {
+            config.decay = decay;
+        }
+        if let Some(profile) = &self.profile {
+            config.profile = profile.clone();
+        }
+        if let Some(vault_dir) = &self.vault_dir {
+            config.vault_dir = Some(expand_home(vault_dir));
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    config: Config,
+    file: Layer,
+    /// Everything but the file, so it can be re-applied when the file is rewritten.
+    overrides: Layer,
+}
+
+static STATE: RwLock<Option<State>> = RwLock::new(None);
+
+pub fn path() -> PathBuf {
+    dirs::config_dir()
+        .expect("No config directory")
+        .join("acts")
+        .join("config.toml")
+}
+
+/// The config file's contents. A missing file is the same as an empty one.
+pub fn read_file() -> Result<(Layer, Vec<String>), String> {
+    match std::fs::read_to_string(path()) {
+        Ok(s) => Layer::parse(&s).map_err(|e| format!("{}: {}", path().display(), e)),
+        Err(_) => Ok((Layer::default(), vec![])),
+    }
+}
+
+/// Loads the config file, applying the environment and `cli` overrides on top. Problems
+/// are printed as warnings and the affected settings keep their defaults.
+pub fn init(cli: Layer) {
+    let (mut overrides, mut warnings) = Layer::from_env();
+    overrides.merge(cli);
+
+    let file = match read_file() {
+        Ok((file, file_warnings)) => {
+            warnings.extend(file_warnings);
+            file
+        }
+        Err(e) => {
+            warnings.push(e);
+            Layer::default()
+        }
+    };
+
+    for warning in warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    *STATE.write().unwrap() = Some(State {
+        overrides,
+        ..Default::default()
+    });
+    apply_file(&file);
+}
+
+/// Switches to another profile until the app is closed.
+pub fn switch_profile(name: &str) {
+    let file = {
+        let mut state = STATE.write().unwrap();
+        let state = state.get_or_insert_with(State::default);
+        state.overrides.profile = Some(name.to_string());
+        state.file.clone()
+    };
+    apply_file(&file);
+}
+
+/// Writes the config file and applies it, keeping the overrides.
+pub fn save(file: &Layer) -> Result<(), String> {
+    let path = path();
+    std::fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+    let s = toml::to_string(file).map_err(|e| e.to_string())?;
+    std::fs::write(&path, s).map_err(|e| e.to_string())?;
+
+    apply_file(file);
+    Ok(())
+}
+
+fn apply_file(file: &Layer) {
+    let mut state = STATE.write().unwrap();
+    let state = state.get_or_insert_with(State::default);
+
+    let mut config = Config::default();
+    file.apply(&mut config);
+    if let Some(profile) = &state.overrides.profile {
+        config.profile = profile.clone();
+    }
+    if config.profile != crate::profile::DEFAULT {
+        config.db_path = None;
+        config.notes_dir = None;
+    }
+    state.overrides.apply(&mut config);
+    state.file = file.clone();
+    state.config = config;
+}
+
+/// The settings in effect. Without [`init`], like in tests, these are the defaults.
+pub fn get() -> Settings {
+    let mut state = STATE.read().unwrap();
+    if state.is_none() {
+        drop(state);
+        apply_file(&Layer::default());
+        state = STATE.read().unwrap();
+    }
+    Settings(state)
+}
+
+/// A read lock on the settings in effect.
+pub struct Settings(RwLockReadGuard<'static, Option<State>>);
+
+impl std::ops::Deref for Settings {
+    type Target = Config;
+
+    fn deref(&self) -> &Config {
+        &self.0.as_ref().unwrap().config
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().expect("No home directory").join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_warns_about_unknown_keys() {
+        let (layer, warnings) =
+            Layer::parse("decay = 0.98\ntheme = \"light\"\ncolour = 1\n").unwrap();
+
+        assert_eq!(layer.decay, Some(0.98));
+        assert_eq!(layer.theme, Some(Theme::Light));
+        assert_eq!(layer.db_path, None);
+        assert_eq!(warnings, vec!["Unknown setting in config file: colour"]);
+        assert!(Layer::parse("decay = ").is_err());
+    }
+
+    #[test]
+    fn test_parse_skips_invalid_values() {
+        let (layer, warnings) =
+            Layer::parse("decay = \"lots\"\nbudget_hours = -1\ntheme = \"light\"\n").unwrap();
+
+        assert_eq!(layer.decay, None);
+        assert_eq!(layer.budget_hours, None);
+        assert_eq!(layer.theme, Some(Theme::Light));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("Invalid value for budget_hours"));
+        assert!(warnings[1].starts_with("Invalid value for decay"));
+
+        let config = Config::default();
+        assert_eq!(config.db_path, None);
+        assert_eq!(config.notes_dir, None);
+    }
+}
@@ -0,0 +1,77 @@
+//! CSV export of sessions, one row per session, for spreadsheets.
+//!
+//! Columns: `date` and `time` of when the session started (UTC), `duration_minutes`,
+//! `activity`, `path` (the activity's ancestors joined by '/'), `activity_id` and
+//! `weighted_minutes` (the duration after the decay that priorities are calculated with).
+
+use super::Filter;
+use crate::activity::Activity;
+use crate::utils::UtcDateTime;
+use crate::Conn;
+
+pub fn export(conn: &Conn, filter: &Filter) -> String {
+    let now = crate::utils::current_unix().as_secs();
+    let mut writer = csv::Writer::from_writer(vec![]);
+
+    writer
+        .write_record([
+            "date",
+            "time",
+            "duration_minutes",
+            "activity",
+            "path",
+            "activity_id",
+            "weighted_minutes",
+        ])
+        .unwrap();
+
+    for session in filter.sessions(conn) {
+        let start = UtcDateTime::from_unix(session.start());
+        let activity = Activity::fetch_activity(conn, session.id).unwrap();
+
+        writer
+            .write_record([
+                start.date(),
+                start.time(),
+                format!("{:.2}", session.minutes()),
+                activity.text,
+                Activity::path(conn, session.id),
+                session.id.to_string(),
+                format!("{:.2}", session.weighted_minutes(now)),
+            ])
+            .unwrap();
+    }
+
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Session;
+
+    #[test]
+    fn test_no_double_counting() {
+        let conn = crate::sql::init_in_memory();
+        let parent = Activity::new("Music".to_string(), None);
+        let child = Activity::new("Piano".to_string(), Some(parent.id));
+        crate::sql::new_activity(&conn, &parent).unwrap();
+        crate::sql::new_activity(&conn, &child).unwrap();
+        Session::log(&conn, child.id, 30., 1678752000 + 1800);
+
+        let csv = export(&conn, &Filter::default());
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[1].starts_with(&format!(
+            "2023-03-14,00:00:00,30.00,Piano,Music/Piano,{}",
+            child.id
+        )));
+
+        let filter = Filter {
+            subtree: Some(child.id),
+            to: Some(1678752000),
+            ..Default::default()
+        };
+        assert_eq!(export(&conn, &filter).lines().count(), 1);
+    }
+}
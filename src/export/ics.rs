@@ -0,0 +1,226 @@
+//! iCalendar (RFC 5545) export of logged sessions and planned slots.
+//!
+//! Every session's event gets a UID derived from when the session ended and how long it
+//! was, which it keeps when it's merged into another activity or handed to a child by a
+//! split, and every slot's from the slot's id. Importing a newer export into a calendar
+//! app updates the existing events rather than adding duplicates.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::Filter;
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::slots::Slot;
+use crate::utils::UtcDateTime;
+use crate::ActID;
+use crate::Conn;
+
+pub fn export(conn: &Conn, filter: &Filter) -> String {
+    let now = crate::utils::current_unix().as_secs();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//acts//acts//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:acts".to_string(),
+    ];
+
+    let sessions = filter.sessions(conn);
+    for (session, uid) in sessions.iter().zip(session_uids(conn, &sessions)) {
+        let summary = Activity::path(conn, session.id);
+        lines.extend(vevent(session, &uid, &summary, now));
+    }
+    for slot in filter.slots(conn) {
+        let summary = format!("Planned: {}", Activity::path(conn, slot.activity));
+        lines.extend(slot_vevent(&slot, &summary, now));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut output = String::new();
+    for line in lines {
+        output.push_str(&fold(&line));
+        output.push_str("\r\n");
+    }
+    output
+}
+
+/// The UIDs of `sessions`, in their order. Sessions that ended at the same time and were
+/// as long are numbered, in the order of their activities' ids among all sessions, so the
+/// UIDs don't depend on the filter.
+fn session_uids(conn: &Conn, sessions: &[Session]) -> Vec<String> {
+    let key = |session: &Session| (session.timestamp, session.duration.as_millis());
+
+    let mut all = Session::fetch_direct_sessions(conn);
+    all.sort_by_key(|session| (key(session), session.id));
+    let mut counts: HashMap<(u64, u128), usize> = HashMap::new();
+    let mut numbers: HashMap<(ActID, u64, u128), VecDeque<usize>> = HashMap::new();
+    for session in &all {
+        let (timestamp, millis) = key(session);
+        let count = counts.entry((timestamp, millis)).or_default();
+        numbers
+            .entry((session.id, timestamp, millis))
+            .or_default()
+            .push_back(*count);
+        *count += 1;
+    }
+
+    sessions
+        .iter()
+        .map(|session| {
+            let (timestamp, millis) = key(session);
+            let nth = numbers
+                .get_mut(&(session.id, timestamp, millis))
+                .and_then(|numbers| numbers.pop_front())
+                .unwrap_or(0);
+            match nth {
+                0 => format!("session-{}-{}@acts", timestamp, millis),
+                nth => format!("session-{}-{}-{}@acts", timestamp, millis, nth),
+            }
+        })
+        .collect()
+}
+
+fn vevent(session: &Session, uid: &str, summary: &str, now: u64) -> Vec<String> {
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", ics_time(now)),
+        format!("DTSTART:{}", ics_time(session.start())),
+        format!("DTEND:{}", ics_time(session.timestamp)),
+        format!("SUMMARY:{}", escape(summary)),
+        format!("X-ACTS-ACTIVITY:{}", session.id),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+fn slot_vevent(slot: &Slot, summary: &str, now: u64) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:slot-{}@acts", slot.id),
+        format!("DTSTAMP:{}", ics_time(now)),
+        format!("DTSTART:{}", ics_time(slot.start)),
+        format!("DTEND:{}", ics_time(slot.end())),
+        format!("SUMMARY:{}", escape(summary)),
+        "STATUS:TENTATIVE".to_string(),
+        format!("X-ACTS-ACTIVITY:{}", slot.activity),
+    ];
+    if !slot.title.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape(&slot.title)));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+fn ics_time(unix: u64) -> String {
+    let dt = UtcDateTime::from_unix(unix);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Content lines may not be longer than 75 octets, longer ones are continued on the next
+/// line with a leading space.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_and_fold() {
+        assert_eq!(escape("a,b;c\\d"), "a\\,b\\;c\\\\d");
+
+        let long = "x".repeat(100);
+        let folded = fold(&long);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1], format!(" {}", "x".repeat(25)));
+    }
+
+    #[test]
+    fn test_export_slots() {
+        let conn = crate::sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        crate::sql::new_activity(&conn, &music).unwrap();
+        let slot = Slot::new(
+            music.id,
+            1678752000,
+            std::time::Duration::from_secs(3600),
+            "Scales".to_string(),
+        );
+        slot.add(&conn);
+
+        let ics = export(&conn, &Filter::default());
+        assert!(ics.contains(&format!("UID:slot-{}@acts", slot.id)));
+        assert!(ics.contains("DTEND:20230314T010000Z"));
+        assert!(ics.contains("SUMMARY:Planned: Music"));
+
+        let filter = Filter {
+            to: Some(1678752000),
+            ..Filter::default()
+        };
+        assert!(!export(&conn, &filter).contains("BEGIN:VEVENT"));
+    }
+
+    #[test]
+    fn test_uids_survive_merge() {
+        let conn = crate::sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        let piano = Activity::new("Piano".to_string(), Some(music.id));
+        let keys = Activity::new("Keys".to_string(), Some(music.id));
+        for act in [&music, &piano, &keys] {
+            crate::sql::new_activity(&conn, act).unwrap();
+        }
+        Session::log(&conn, piano.id, 30., 1678752000);
+        Session::log(&conn, keys.id, 30., 1678752000);
+
+        // Which of the two gets the number depends on their ids, so they're compared sorted.
+        let uids = |conn: &Conn| -> Vec<String> {
+            let mut uids: Vec<String> = export(conn, &Filter::default())
+                .lines()
+                .filter_map(|line| line.strip_prefix("UID:"))
+                .map(str::to_string)
+                .collect();
+            uids.sort();
+            uids
+        };
+        let before = uids(&conn);
+        assert_eq!(
+            before,
+            vec![
+                "session-1678752000-1800000-1@acts",
+                "session-1678752000-1800000@acts"
+            ]
+        );
+
+        crate::merge::merge(&conn, piano.id, keys.id).unwrap();
+        assert_eq!(uids(&conn), before);
+    }
+
+    #[test]
+    fn test_ics_time() {
+        assert_eq!(ics_time(1678752000), "20230314T000000Z");
+    }
+}
@@ -0,0 +1,114 @@
+//! The acts JSON interchange format.
+//!
+//! A document holds the whole database: every activity with its note, and every session.
+//!
+//! ```json
+//! {
+//!   "format": "acts",
+//!   "version": 2,
+//!   "exported_at": 1678752000,
+//!   "activities": [
+//!     {
+//!       "id": "5f0c6a2e-6a4f-4f4e-9d8e-0d5c0a6f1b2c",
+//!       "text": "Music",
+//!       "parent": null,
+//!       "assigned": 40,
+//!       "note": "Practice scales first."
+//!     }
+//!   ],
+//!   "sessions": [
+//!     {
+//!       "activity": "5f0c6a2e-6a4f-4f4e-9d8e-0d5c0a6f1b2c",
+//!       "duration": 45.0,
+//!       "timestamp": 1678790000
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! - `version` is bumped whenever a change would make older readers misinterpret a document.
+//! - `exported_at` and `timestamp` are unix seconds (UTC). A session's `timestamp` is
+//!   when it ended, `duration` is in minutes.
+//! - `parent` is the id of another activity in the document, or `null` for a root.
+//! - `assigned` is the percentage of the parent's time the activity should get.
+//! - `note` is `null` when the activity has no note.
+//! - `archived` is only there, as `true`, for archived activities. It was added in version
+//!   2, version 1 documents are read as having no archived activities.
+//! - Activities are listed in the order of the tree, each one before its children, and
+//!   siblings keep the order they're listed in on import.
+//! - Each session appears once, on the activity it was logged on. The copies that logging
+//!   keeps on the ancestors are left out and recreated on import.
+
+use serde::{Deserialize, Serialize};
+
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::ActID;
+use crate::Conn;
+
+pub const FORMAT: &str = "acts";
+pub const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Document {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: u64,
+    pub activities: Vec<ActivityEntry>,
+    pub sessions: Vec<SessionEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub id: ActID,
+    pub text: String,
+    pub parent: Option<ActID>,
+    pub assigned: u32,
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEntry {
+    pub activity: ActID,
+    pub duration: f64,
+    pub timestamp: u64,
+}
+
+impl Document {
+    pub fn from_db(conn: &Conn) -> Self {
+        let activities = Activity::fetch_all_in_tree_order(conn)
+            .into_iter()
+            .map(|act| ActivityEntry {
+                note: crate::notes::read(conn, act.id),
+                id: act.id,
+                text: act.text,
+                parent: act.parent,
+                assigned: act.assigned,
+                archived: act.archived,
+            })
+            .collect();
+
+        let sessions = Session::fetch_direct_sessions(conn)
+            .into_iter()
+            .map(|session| SessionEntry {
+                activity: session.id,
+                duration: session.minutes(),
+                timestamp: session.timestamp,
+            })
+            .collect();
+
+        Self {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            exported_at: crate::utils::current_unix().as_secs(),
+            activities,
+            sessions,
+        }
+    }
+}
+
+pub fn export(conn: &Conn) -> String {
+    serde_json::to_string_pretty(&Document::from_db(conn)).unwrap()
+}
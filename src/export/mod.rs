@@ -0,0 +1,102 @@
+pub mod csv;
+pub mod ics;
+pub mod json;
+pub mod outline;
+
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::slots::Slot;
+use crate::ActID;
+use crate::Conn;
+
+/// The file formats the export page can write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ics,
+    Json,
+    Csv,
+    Markdown,
+    Opml,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Ics => "ics",
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Markdown => "md",
+            Format::Opml => "opml",
+        }
+    }
+}
+
+/// Narrows down which sessions end up in an export.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Only sessions logged at or after this unix time.
+    pub from: Option<u64>,
+    /// Only sessions logged before this unix time.
+    pub to: Option<u64>,
+    /// Only sessions on this activity or its descendants.
+    pub subtree: Option<ActID>,
+}
+
+impl Filter {
+    pub fn contains_time(&self, timestamp: u64) -> bool {
+        self.from.is_none_or(|from| timestamp >= from) && self.to.is_none_or(|to| timestamp < to)
+    }
+
+    /// The activities that pass the subtree filter, or all of them.
+    pub fn activities(&self, conn: &Conn) -> Vec<ActID> {
+        match self.subtree {
+            Some(id) => Activity::subtree_ids(conn, id),
+            None => Activity::fetch_all_activities_flat(conn)
+                .into_iter()
+                .map(|act| act.id)
+                .collect(),
+        }
+    }
+
+    /// Every session that passes the filter, counted once regardless of how many
+    /// ancestors it was logged on.
+    pub fn sessions(&self, conn: &Conn) -> Vec<Session> {
+        let ids = self.activities(conn);
+        Session::fetch_direct_sessions(conn)
+            .into_iter()
+            .filter(|session| ids.contains(&session.id) && self.contains_time(session.timestamp))
+            .collect()
+    }
+
+    /// Every planned slot that passes the filter, by the time it starts.
+    pub fn slots(&self, conn: &Conn) -> Vec<Slot> {
+        let ids = self.activities(conn);
+        Slot::fetch_all(conn)
+            .into_iter()
+            .filter(|slot| ids.contains(&slot.activity) && self.contains_time(slot.start))
+            .collect()
+    }
+}
+
+/// Writes the export into `dir`, returning the path of the written file.
+pub fn export_to_dir(
+    conn: &Conn,
+    format: Format,
+    filter: &Filter,
+    dir: &std::path::Path,
+) -> std::io::Result<std::path::PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let mut path = dir.to_path_buf();
+    path.push(format!("acts.{}", format.extension()));
+
+    let contents = match format {
+        Format::Ics => ics::export(conn, filter),
+        Format::Json => json::export(conn),
+        Format::Csv => csv::export(conn, filter),
+        Format::Markdown => outline::to_markdown(&outline::nodes(conn, filter.subtree)),
+        Format::Opml => outline::to_opml(&outline::nodes(conn, filter.subtree)),
+    };
+
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
@@ -0,0 +1,160 @@
+//! Exports the activity tree as an outline, either a nested Markdown list or OPML.
+//!
+//! Each item's text ends with its assigned percentage, like `Piano [60%]`, and notes are
+//! included. Items also carry their activity's id (an HTML comment in Markdown, an `actsId`
+//! attribute in OPML) so that an edited outline can be imported back onto the same
+//! activities.
+
+use crate::activity::Activity;
+use crate::ActID;
+use crate::Conn;
+
+/// An activity and its descendants, as they appear in an outline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: Option<ActID>,
+    pub text: String,
+    pub assigned: Option<u32>,
+    pub note: Option<String>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn from_db(conn: &Conn, activity: Activity) -> Self {
+        Self {
+            id: Some(activity.id),
+            note: crate::notes::read(conn, activity.id),
+            assigned: Some(activity.assigned),
+            children: Activity::fetch_children(conn, Some(activity.id))
+                .into_iter()
+                .map(|kid| Node::from_db(conn, kid))
+                .collect(),
+            text: activity.text,
+        }
+    }
+
+    /// The text along with the `[40%]` annotation.
+    fn label(&self) -> String {
+        match self.assigned {
+            Some(assigned) => format!("{} [{}%]", self.text, assigned),
+            None => self.text.clone(),
+        }
+    }
+
+    /// Splits a `[40%]` annotation off the end of an item's text.
+    pub fn parse_label(label: &str) -> (String, Option<u32>) {
+        let label = label.trim();
+        if let Some(rest) = label.strip_suffix("%]") {
+            if let Some(idx) = rest.rfind('[') {
+                if let Ok(assigned) = rest[idx + 1..].trim().parse::<u32>() {
+                    return (rest[..idx].trim_end().to_string(), Some(assigned));
+                }
+            }
+        }
+        (label.to_string(), None)
+    }
+}
+
+/// The subtree, or every root and its descendants.
+pub fn nodes(conn: &Conn, subtree: Option<ActID>) -> Vec<Node> {
+    let roots = match subtree {
+        Some(id) => vec![Activity::fetch_activity(conn, id).unwrap()],
+        None => Activity::fetch_children(conn, None),
+    };
+    roots
+        .into_iter()
+        .map(|act| Node::from_db(conn, act))
+        .collect()
+}
+
+pub fn to_markdown(nodes: &[Node]) -> String {
+    fn recursive(output: &mut String, node: &Node, depth: usize) {
+        let indent = "  ".repeat(depth);
+        output.push_str(&format!("{}- {}", indent, node.label()));
+        if let Some(id) = node.id {
+            output.push_str(&format!(" <!-- id:{} -->", id));
+        }
+        output.push('\n');
+
+        if let Some(note) = &node.note {
+            for line in note.trim_end().lines() {
+                if line.trim().is_empty() {
+                    output.push('\n');
+                } else {
+                    output.push_str(&format!("{}  {}\n", indent, escape_note_line(line)));
+                }
+            }
+        }
+
+        for child in &node.children {
+            recursive(output, child, depth + 1);
+        }
+    }
+
+    let mut output = String::new();
+    for node in nodes {
+        recursive(&mut output, node, 0);
+    }
+    output
+}
+
+/// Puts a `\` before a note line that would be read back as an item, or that already
+/// starts with such an escape.
+pub fn escape_note_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let item = ["- ", "* ", "+ "]
+        .iter()
+        .any(|bullet| trimmed.starts_with(bullet));
+    let escaped = trimmed
+        .strip_prefix('\\')
+        .is_some_and(|rest| rest.starts_with(['\\', '-', '*', '+']));
+    if item || escaped {
+        format!("{}\\{}", &line[..line.len() - trimmed.len()], trimmed)
+    } else {
+        line.to_string()
+    }
+}
+
+pub fn to_opml(nodes: &[Node]) -> String {
+    fn escape(s: &str) -> String {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\n', "&#10;")
+    }
+
+    fn recursive(output: &mut String, node: &Node, depth: usize) {
+        let indent = "  ".repeat(depth + 2);
+        output.push_str(&format!(
+            "{}<outline text=\"{}\"",
+            indent,
+            escape(&node.label())
+        ));
+        if let Some(note) = &node.note {
+            output.push_str(&format!(" _note=\"{}\"", escape(note.trim_end())));
+        }
+        if let Some(id) = node.id {
+            output.push_str(&format!(" actsId=\"{}\"", id));
+        }
+
+        if node.children.is_empty() {
+            output.push_str("/>\n");
+        } else {
+            output.push_str(">\n");
+            for child in &node.children {
+                recursive(output, child, depth + 1);
+            }
+            output.push_str(&format!("{}</outline>\n", indent));
+        }
+    }
+
+    let mut output = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  <head>\n    <title>acts</title>\n  </head>\n  <body>\n",
+    );
+    for node in nodes {
+        recursive(&mut output, node, 0);
+    }
+    output.push_str("  </body>\n</opml>\n");
+    output
+}
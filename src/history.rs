@@ -1,13 +1,15 @@
+use crate::activity::Activity;
 use crate::sql;
 use crate::ActID;
 use crate::Conn;
 use uuid::Uuid;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Session {
-    _id: ActID,
-    duration: std::time::Duration,
-    timestamp: u64,
+    pub id: ActID,
+    pub duration: std::time::Duration,
+    /// Unix time of when the session was logged, i.e. when it ended.
+    pub timestamp: u64,
 }
 
 impl std::convert::TryFrom<&rusqlite::Row<'_>> for Session {
@@ -15,7 +17,7 @@ impl std::convert::TryFrom<&rusqlite::Row<'_>> for Session {
 
     fn try_from(value: &rusqlite::Row) -> Result<Self, Self::Error> {
         Ok(Self {
-            _id: Uuid::parse_str(&value.get::<usize, String>(0)?).unwrap(),
+            id: Uuid::parse_str(&value.get::<usize, String>(0)?).unwrap(),
             duration: std::time::Duration::from_secs_f64(value.get::<usize, f64>(1)? * 60.),
             timestamp: value.get(2)?,
         })
@@ -34,13 +36,163 @@ impl Session {
         sql::query_map(conn, &statement, |row| Session::try_from(row)).unwrap()
     }
 
+    /// Logs a session on the activity and, so that their totals include it, on all its ancestors.
+    pub fn log(conn: &Conn, id: ActID, minutes: f64, timestamp: u64) {
+        let statement = "INSERT INTO history (id, duration, timestamp) VALUES (?1, ?2, ?3)";
+        let mut id = id;
+
+        loop {
+            conn.execute(
+                statement,
+                rusqlite::params![id.to_string(), minutes, timestamp],
+            )
+            .unwrap();
+
+            match Activity::get_parent(conn, id) {
+                Some(parent) => id = parent.id,
+                None => break,
+            }
+        }
+    }
+
+    /// The minutes logged on the activity and its descendants.
+    pub fn total_minutes(conn: &Conn, id: ActID) -> f64 {
+        let statement = format!(
+            "SELECT IFNULL(SUM(duration), 0) FROM history WHERE id = '{}'",
+            id
+        );
+        sql::query_row(conn, &statement, |row| row.get::<usize, f64>(0)).unwrap()
+    }
+
+    /// Whether the exact session has already been logged on the activity.
+    pub fn exists(conn: &Conn, id: ActID, minutes: f64, timestamp: u64) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) FROM history WHERE id = ?1 AND duration = ?2 AND timestamp = ?3",
+            rusqlite::params![id.to_string(), minutes, timestamp],
+            |row| row.get::<usize, usize>(0),
+        )
+        .unwrap()
+            > 0
+    }
+
+    /// The duration in minutes, which is how it's stored in the database.
+    pub fn minutes(&self) -> f64 {
+        self.duration.as_secs_f64() / 60.
+    }
+
+    /// The duration in minutes, scaled down by how long ago the session was logged. This is
+    /// the amount the session counts towards an activity's priority.
+    pub fn weighted_minutes(&self, unix_now: u64) -> f64 {
+        let diff = std::time::Duration::from_secs(unix_now.saturating_sub(self.timestamp));
+        self.minutes() * Self::get_decay_factor_from_duration(diff) as f64
+    }
+
+    /// Every logged session exactly once, attributed to the activity it was logged on.
+    /// Rows of activities that don't exist anymore are left out.
+    ///
+    /// Logging a session also inserts a copy of it for every ancestor, so a row is skipped
+    /// when one of the activity's children has a row with the same timestamp and duration.
+    pub fn fetch_direct_sessions(conn: &Conn) -> Vec<Session> {
+        // Older versions left the history of deleted activities behind.
+        let statement = "SELECT h.id, h.duration, h.timestamp FROM history h
+            JOIN activities act ON act.id = h.id
+            WHERE NOT EXISTS (
+                SELECT 1 FROM history c JOIN activities a ON a.id = c.id
+                WHERE a.parent = h.id AND c.timestamp = h.timestamp AND c.duration = h.duration
+            )
+            ORDER BY h.timestamp";
+        sql::query_map(conn, statement, |row| Session::try_from(row)).unwrap()
+    }
+
+    /// The sessions logged on the activity itself rather than on one of its descendants.
+    pub fn fetch_direct_sessions_of(conn: &Conn, id: ActID) -> Vec<Session> {
+        let statement = format!(
+            "SELECT h.id, h.duration, h.timestamp FROM history h
+            WHERE h.id = '{}' AND NOT EXISTS (
+                SELECT 1 FROM history c JOIN activities a ON a.id = c.id
+                WHERE a.parent = h.id AND c.timestamp = h.timestamp AND c.duration = h.duration
+            )
+            ORDER BY h.timestamp",
+            id
+        );
+        sql::query_map(conn, &statement, |row| Session::try_from(row)).unwrap()
+    }
+
+    /// The sessions logged on the activity itself as the minutes and timestamps they're
+    /// stored with, which compare equal to their rows unlike the converted [`Session`]s.
+    pub fn fetch_direct_rows_of(conn: &Conn, id: ActID) -> Vec<(f64, u64)> {
+        let statement = format!(
+            "SELECT h.duration, h.timestamp FROM history h
+            WHERE h.id = '{}' AND NOT EXISTS (
+                SELECT 1 FROM history c JOIN activities a ON a.id = c.id
+                WHERE a.parent = h.id AND c.timestamp = h.timestamp AND c.duration = h.duration
+            )
+            ORDER BY h.timestamp",
+            id
+        );
+        sql::query_map(conn, &statement, |row| {
+            Ok((row.get::<usize, f64>(0)?, row.get::<usize, u64>(1)?))
+        })
+        .unwrap()
+    }
+
+    /// Removes one session logged on the activity along with its copies on the ancestors,
+    /// the opposite of [`Session::log`].
+    pub fn unlog(conn: &Conn, id: ActID, minutes: f64, timestamp: u64) {
+        let mut id = Some(id);
+        while let Some(current) = id {
+            conn.execute(
+                "DELETE FROM history WHERE rowid = (SELECT rowid FROM history
+                    WHERE id = ?1 AND duration = ?2 AND timestamp = ?3 LIMIT 1)",
+                rusqlite::params![current.to_string(), minutes, timestamp],
+            )
+            .unwrap();
+            id = Activity::get_parent_index(conn, current);
+        }
+    }
+
+    /// Deletes the sessions logged on the activity, or with `subtree` also those logged on
+    /// its descendants, along with their copies on the ancestors so they aren't counted
+    /// anywhere anymore.
+    pub fn delete_sessions(conn: &Conn, id: ActID, subtree: bool) {
+        let sessions = if subtree {
+            let statement = format!(
+                "SELECT duration, timestamp FROM history WHERE id = '{}'",
+                id
+            );
+            sql::query_map(conn, &statement, |row| {
+                Ok((row.get::<usize, f64>(0)?, row.get::<usize, u64>(1)?))
+            })
+            .unwrap()
+        } else {
+            Self::fetch_direct_rows_of(conn, id)
+        };
+
+        for (duration, timestamp) in sessions {
+            Self::unlog(conn, id, duration, timestamp);
+        }
+
+        if subtree {
+            for id in Activity::subtree_ids(conn, id) {
+                sql::execute(conn, &format!("DELETE FROM history WHERE id = '{}'", id)).unwrap();
+            }
+        }
+    }
+
+    /// Unix time of when the session started.
+    pub fn start(&self) -> u64 {
+        self.timestamp.saturating_sub(self.duration.as_secs())
+    }
+
     fn total_weighted_time_from_sessions(sessions: &Vec<Session>) -> std::time::Duration {
         let unix_now = crate::utils::current_unix();
 
         let mut total_time = std::time::Duration::default();
 
         for session in sessions {
-            let diff = std::time::Duration::from_secs(unix_now.as_secs() - session.timestamp);
+            let diff = std::time::Duration::from_secs(
+                unix_now.as_secs().saturating_sub(session.timestamp),
+            );
             let factor = Self::get_decay_factor_from_duration(diff);
             let time = session.duration.mul_f32(factor);
             total_time += time;
@@ -49,32 +201,36 @@ impl Session {
     }
 
     fn average_daily_weighted_time_from_sessions(sessions: &Vec<Session>) -> std::time::Duration {
-    let unix_now = crate::utils::current_unix();
-
-    let mut total_time = std::time::Duration::default();
-    let mut total_decay_factor = 0f32;
+        let unix_now = crate::utils::current_unix();
 
-    for session in sessions {
-        let diff = std::time::Duration::from_secs(unix_now.as_secs() - session.timestamp);
-        let decay_factor = Self::get_decay_factor_from_duration(diff);
-        let time = session.duration.mul_f32(decay_factor);
+        let mut total_time = std::time::Duration::default();
+        let mut total_decay_factor = 0f32;
 
-        total_time += time;
-        total_decay_factor += decay_factor;
-    }
+        for session in sessions {
+            let diff = std::time::Duration::from_secs(
+                unix_now.as_secs().saturating_sub(session.timestamp),
+            );
+            let decay_factor = Self::get_decay_factor_from_duration(diff);
+            let time = session.duration.mul_f32(decay_factor);
 
-    if total_decay_factor == 0f32 {
-       return std::time::Duration::default();
-    }
+            total_time += time;
+            total_decay_factor += decay_factor;
+        }
 
-    total_time
-}
+        if total_decay_factor == 0f32 {
+            return std::time::Duration::default();
+        }
 
-pub fn average_daily_weighted_time_spent_from_activity(conn: &Conn, id: ActID) -> std::time::Duration {
-    let sessions = Self::get_history(conn, id);
-    Self::average_daily_weighted_time_from_sessions(&sessions)
-}
+        total_time
+    }
 
+    pub fn average_daily_weighted_time_spent_from_activity(
+        conn: &Conn,
+        id: ActID,
+    ) -> std::time::Duration {
+        let sessions = Self::get_history(conn, id);
+        Self::average_daily_weighted_time_from_sessions(&sessions)
+    }
 
     pub fn total_weighted_time_spent_from_activity(conn: &Conn, id: ActID) -> std::time::Duration {
         let sessions = Self::get_history(conn, id);
@@ -83,7 +239,7 @@ pub fn average_daily_weighted_time_spent_from_activity(conn: &Conn, id: ActID) -
 
     fn get_decay_factor_from_duration(duration: std::time::Duration) -> f32 {
         let days = duration.as_secs_f32() / 86400.;
-        std::f32::consts::E.powf(0.99f32.ln() * days)
+        std::f32::consts::E.powf(crate::config::get().decay.ln() * days)
     }
 }
 
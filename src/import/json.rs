@@ -0,0 +1,374 @@
+//! Imports documents in the format described in [`crate::export::json`].
+
+use std::collections::{HashMap, HashSet};
+
+use super::{Mode, Summary};
+use crate::activity::Activity;
+use crate::export::json::{ActivityEntry, Document, FORMAT, VERSION};
+use crate::history::Session;
+use crate::ActID;
+use crate::Conn;
+
+/// Parses and validates a document.
+pub fn parse(s: &str) -> Result<Document, String> {
+    let doc: Document = serde_json::from_str(s).map_err(|e| e.to_string())?;
+
+    if doc.format != FORMAT {
+        return Err(format!("Not an acts export: format is '{}'", doc.format));
+    }
+    if doc.version > VERSION {
+        return Err(format!(
+            "Export has version {}, but this version of acts only reads up to {}",
+            doc.version, VERSION
+        ));
+    }
+
+    let ids: HashSet<ActID> = doc.activities.iter().map(|act| act.id).collect();
+    if ids.len() != doc.activities.len() {
+        return Err("An activity id appears more than once".to_string());
+    }
+    for session in &doc.sessions {
+        if !ids.contains(&session.activity) {
+            return Err(format!("Session on unknown activity {}", session.activity));
+        }
+    }
+
+    let parents = doc
+        .activities
+        .iter()
+        .map(|act| (act.id, act.parent))
+        .collect();
+    check_cycles(&parents, &doc)?;
+
+    Ok(doc)
+}
+
+/// Fails if following the parents from one of the document's activities leads back to it,
+/// which would make every walk up the tree loop forever.
+fn check_cycles(parents: &HashMap<ActID, Option<ActID>>, doc: &Document) -> Result<(), String> {
+    for act in &doc.activities {
+        let mut next = parents.get(&act.id).copied().flatten();
+        let mut steps = 0;
+        while let Some(id) = next {
+            if id == act.id || steps > parents.len() {
+                return Err(format!("The parents of '{}' go round in a cycle", act.text));
+            }
+            next = parents.get(&id).copied().flatten();
+            steps += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Imports the document, or only reports what would change if `dry_run` is set.
+pub fn import(conn: &Conn, doc: &Document, mode: Mode, dry_run: bool) -> Result<Summary, String> {
+    let existing: HashMap<ActID, Activity> = Activity::fetch_all_activities_flat(conn)
+        .into_iter()
+        .map(|act| (act.id, act))
+        .collect();
+    let imported: HashSet<ActID> = doc.activities.iter().map(|act| act.id).collect();
+
+    for act in &doc.activities {
+        if let Some(parent) = act.parent {
+            let known = imported.contains(&parent)
+                || (mode == Mode::Merge && existing.contains_key(&parent));
+            if !known {
+                return Err(format!(
+                    "Activity '{}' has unknown parent {}",
+                    act.text, parent
+                ));
+            }
+        }
+    }
+
+    let mut parents: HashMap<ActID, Option<ActID>> = match mode {
+        Mode::Merge => existing.values().map(|act| (act.id, act.parent)).collect(),
+        Mode::Replace => HashMap::new(),
+    };
+    parents.extend(doc.activities.iter().map(|act| (act.id, act.parent)));
+    check_cycles(&parents, doc)?;
+
+    let mut summary = Summary::default();
+
+    let mut added = vec![];
+    let mut updated = vec![];
+    for act in &doc.activities {
+        match existing.get(&act.id) {
+            None => added.push(act),
+            Some(old)
+                if old.text != act.text
+                    || old.parent != act.parent
+                    || old.assigned != act.assigned
+                    || old.archived != act.archived =>
+            {
+                updated.push(act)
+            }
+            Some(_) => {}
+        }
+    }
+    summary.activities_added = added.len();
+    summary.activities_updated = updated.len();
+
+    let removed: Vec<ActID> = match mode {
+        Mode::Merge => vec![],
+        Mode::Replace => existing
+            .keys()
+            .filter(|id| !imported.contains(id))
+            .copied()
+            .collect(),
+    };
+    summary.activities_removed = removed.len();
+
+    let sessions: Vec<_> = match mode {
+        Mode::Merge => doc
+            .sessions
+            .iter()
+            .filter(|s| !Session::exists(conn, s.activity, s.duration, s.timestamp))
+            .collect(),
+        Mode::Replace => doc.sessions.iter().collect(),
+    };
+    summary.sessions_added = sessions.len();
+    if mode == Mode::Replace {
+        summary.sessions_removed = Session::fetch_direct_sessions(conn).len();
+    }
+
+    let notes: Vec<&ActivityEntry> = doc
+        .activities
+        .iter()
+        .filter(|act| act.note.is_some() && act.note != crate::notes::read(conn, act.id))
+        .collect();
+    summary.notes_written = notes.len();
+
+    let stale_notes: Vec<ActID> = match mode {
+        Mode::Merge => vec![],
+        Mode::Replace => existing
+            .keys()
+            .filter(|id| {
+                crate::notes::read(conn, **id).is_some()
+                    && doc
+                        .activities
+                        .iter()
+                        .all(|act| act.id != **id || act.note.is_none())
+            })
+            .copied()
+            .collect(),
+    };
+    summary.notes_removed = stale_notes.len();
+
+    if dry_run {
+        return Ok(summary);
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    if mode == Mode::Replace {
+        tx.execute("DELETE FROM history", [])
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM activities", [])
+            .map_err(|e| e.to_string())?;
+    }
+
+    // Siblings are numbered in the order they're listed.
+    let mut positions: HashMap<Option<ActID>, u32> = HashMap::new();
+    for act in &doc.activities {
+        let position = positions.entry(act.parent).or_default();
+        *position += 1;
+        let statement = if mode == Mode::Replace || !existing.contains_key(&act.id) {
+            "INSERT INTO activities (id, text, parent, assigned, archived, created_at, updated_at,
+                position) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7)"
+        } else {
+            "UPDATE activities SET text = ?2, parent = ?3, assigned = ?4, archived = ?5,
+                updated_at = CASE WHEN text IS ?2 AND parent IS ?3 AND assigned = ?4
+                    AND archived = ?5 THEN updated_at ELSE ?6 END, position = ?7
+                WHERE id = ?1"
+        };
+        tx.execute(
+            statement,
+            rusqlite::params![
+                act.id.to_string(),
+                act.text,
+                act.parent.map(|p| p.to_string()),
+                act.assigned,
+                act.archived,
+                crate::utils::current_unix().as_secs(),
+                *position - 1
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    for session in sessions {
+        Session::log(conn, session.activity, session.duration, session.timestamp);
+    }
+
+    for act in notes {
+        crate::notes::write(conn, act.id, act.note.as_ref().unwrap());
+    }
+    for id in stale_notes {
+        crate::notes::delete(conn, id);
+    }
+    if mode == Mode::Replace {
+        // A running timer, planned slots and collapsed state only stay for the activities
+        // that are still there.
+        tx.execute_batch(
+            "DELETE FROM notes WHERE id NOT IN (SELECT id FROM activities);
+            DELETE FROM timer WHERE id NOT IN (SELECT id FROM activities);
+            DELETE FROM slots WHERE activity NOT IN (SELECT id FROM activities);
+            DELETE FROM collapsed WHERE id NOT IN (SELECT id FROM activities);",
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::json::SessionEntry;
+
+    fn doc() -> Document {
+        let parent = uuid::Uuid::new_v4();
+        let child = uuid::Uuid::new_v4();
+        Document {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            exported_at: 0,
+            activities: vec![
+                ActivityEntry {
+                    id: parent,
+                    text: "Music".to_string(),
+                    parent: None,
+                    assigned: 100,
+                    note: None,
+                    archived: false,
+                },
+                ActivityEntry {
+                    id: child,
+                    text: "Piano".to_string(),
+                    parent: Some(parent),
+                    assigned: 100,
+                    note: None,
+                    archived: false,
+                },
+            ],
+            sessions: vec![SessionEntry {
+                activity: child,
+                duration: 30.,
+                timestamp: 1000,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let conn = crate::sql::init_in_memory();
+        let doc = doc();
+
+        let summary = import(&conn, &doc, Mode::Replace, false).unwrap();
+        assert_eq!(summary.activities_added, 2);
+        assert_eq!(summary.sessions_added, 1);
+
+        // The session is also logged on the parent, but exported once.
+        assert_eq!(Session::get_history(&conn, doc.activities[0].id).len(), 1);
+        let exported = Document::from_db(&conn);
+        assert_eq!(exported.sessions, doc.sessions);
+
+        // Merging the same document again changes nothing.
+        let summary = import(&conn, &doc, Mode::Merge, false).unwrap();
+        assert_eq!(summary, Summary::default());
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let conn = crate::sql::init_in_memory();
+        let summary = import(&conn, &doc(), Mode::Merge, true).unwrap();
+        assert_eq!(summary.activities_added, 2);
+        assert!(Activity::fetch_all_activities_flat(&conn).is_empty());
+    }
+
+    #[test]
+    fn test_rejects_cycles() {
+        let mut doc = doc();
+        doc.activities[0].parent = Some(doc.activities[1].id);
+        let s = serde_json::to_string(&doc).unwrap();
+        assert!(parse(&s).unwrap_err().contains("cycle"));
+
+        // Music gets Piano as its parent in the file, but Piano is Music's child in the
+        // database.
+        let conn = crate::sql::init_in_memory();
+        let mut doc = self::doc();
+        import(&conn, &doc, Mode::Replace, false).unwrap();
+        let piano = doc.activities[1].id;
+        doc.activities.truncate(1);
+        doc.sessions.clear();
+        doc.activities[0].parent = Some(piano);
+        let s = serde_json::to_string(&doc).unwrap();
+        let doc = parse(&s).unwrap();
+        assert!(import(&conn, &doc, Mode::Merge, true)
+            .unwrap_err()
+            .contains("cycle"));
+    }
+
+    #[test]
+    fn test_skips_orphaned_sessions() {
+        let conn = crate::sql::init_in_memory();
+        let doc = doc();
+        import(&conn, &doc, Mode::Replace, false).unwrap();
+        crate::sql::delete_activity(&conn, doc.activities[1].id);
+
+        // The session now counts as Music's, and Piano's row is left out.
+        let exported = Document::from_db(&conn);
+        assert_eq!(exported.sessions.len(), 1);
+        assert_eq!(exported.sessions[0].activity, doc.activities[0].id);
+        let s = serde_json::to_string(&exported).unwrap();
+        assert!(parse(&s).is_ok());
+    }
+
+    #[test]
+    fn test_replace_with_running_timer() {
+        let conn = crate::sql::init_in_memory();
+        let doc = doc();
+        let drums = Activity::new("Drums".to_string(), None);
+        crate::sql::new_activity(&conn, &drums).unwrap();
+        crate::timer::Timer::start(&conn, drums.id);
+        crate::sql::set_collapsed(&conn, drums.id, true);
+        let slot = crate::slots::Slot::new(
+            drums.id,
+            1000,
+            std::time::Duration::from_secs(600),
+            String::new(),
+        );
+        slot.add(&conn);
+
+        import(&conn, &doc, Mode::Replace, false).unwrap();
+        assert!(crate::timer::Timer::running(&conn).is_none());
+        assert!(crate::report::TimerReport::running(&conn).is_none());
+        assert!(crate::slots::Slot::fetch_all(&conn).is_empty());
+        assert!(crate::sql::fetch_collapsed(&conn).is_empty());
+
+        // A timer on an activity that's in the document keeps running.
+        crate::timer::Timer::start(&conn, doc.activities[1].id);
+        import(&conn, &doc, Mode::Replace, false).unwrap();
+        assert!(crate::timer::Timer::running(&conn).is_some());
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let mut doc = doc();
+        doc.version = VERSION + 1;
+        let s = serde_json::to_string(&doc).unwrap();
+        assert!(parse(&s).is_err());
+
+        // Version 1 didn't have archived activities yet.
+        doc.version = 1;
+        let s = serde_json::to_string(&doc).unwrap();
+        assert!(parse(&s)
+            .unwrap()
+            .activities
+            .iter()
+            .all(|act| !act.archived));
+    }
+}
@@ -0,0 +1,117 @@
+pub mod json;
+pub mod outline;
+pub mod timelog;
+pub mod timewarrior;
+pub mod toggl;
+pub mod watson;
+
+use crate::ActID;
+use crate::Conn;
+
+/// The file formats the import page can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The JSON format acts exports.
+    Acts,
+    /// `timew export` output.
+    Timewarrior,
+    /// Watson's frames file.
+    Watson,
+    /// Toggl Track's detailed report CSV.
+    Toggl,
+    /// A nested Markdown bullet list.
+    Markdown,
+    /// An OPML outline.
+    Opml,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [
+        Format::Acts,
+        Format::Timewarrior,
+        Format::Watson,
+        Format::Toggl,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Acts => "acts JSON",
+            Format::Timewarrior => "Timewarrior",
+            Format::Watson => "Watson",
+            Format::Toggl => "Toggl CSV",
+            Format::Markdown => "Markdown outline",
+            Format::Opml => "OPML",
+        }
+    }
+}
+
+/// What to do with the data that's already in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Keep existing data, adding what's new and updating activities with the same id.
+    Merge,
+    /// Throw away existing data and replace it with the imported data.
+    Replace,
+}
+
+/// What an import changed, or would change in a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub activities_added: usize,
+    pub activities_updated: usize,
+    pub activities_removed: usize,
+    pub sessions_added: usize,
+    pub sessions_removed: usize,
+    pub notes_written: usize,
+    pub notes_removed: usize,
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "activities: {} added, {} updated, {} removed; sessions: {} added, {} removed; notes: {} written, {} removed",
+            self.activities_added,
+            self.activities_updated,
+            self.activities_removed,
+            self.sessions_added,
+            self.sessions_removed,
+            self.notes_written,
+            self.notes_removed,
+        )
+    }
+}
+
+/// Reads the file and imports it. Outlines and time logs from other trackers are always
+/// merged into the existing data, below `parent`. `mode` only applies to acts' own format,
+/// and `parent` doesn't since those documents are a whole tree.
+pub fn import_file(
+    conn: &Conn,
+    format: Format,
+    path: &std::path::Path,
+    mode: Mode,
+    parent: Option<ActID>,
+    dry_run: bool,
+) -> Result<Summary, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    let entries = match format {
+        Format::Acts => {
+            let doc = json::parse(&contents)?;
+            return json::import(conn, &doc, mode, dry_run);
+        }
+        Format::Timewarrior => timewarrior::parse(&contents)?,
+        Format::Watson => watson::parse(&contents)?,
+        Format::Toggl => toggl::parse(&contents)?,
+        Format::Markdown => {
+            let nodes = outline::parse_markdown(&contents);
+            return Ok(outline::import(conn, &nodes, parent, dry_run));
+        }
+        Format::Opml => {
+            let nodes = outline::parse_opml(&contents)?;
+            return Ok(outline::import(conn, &nodes, parent, dry_run));
+        }
+    };
+
+    Ok(timelog::import(conn, &entries, parent, dry_run))
+}
@@ -0,0 +1,333 @@
+//! Imports a nested Markdown list or an OPML file as a subtree.
+//!
+//! This reads what [`crate::export::outline`] writes, but hand-written outlines work too:
+//! ids and `[40%]` annotations are optional. Items with the id of an existing activity
+//! update and move that activity, all others become new activities.
+
+use std::collections::HashSet;
+
+use super::Summary;
+use crate::activity::Activity;
+use crate::export::outline::Node;
+use crate::ActID;
+use crate::Conn;
+
+/// Parses lines starting with `-`, `*` or `+` as items, nested by indentation. Other
+/// indented lines below an item are its note, where a leading `\` keeps a line that
+/// looks like an item in the note.
+pub fn parse_markdown(s: &str) -> Vec<Node> {
+    fn indentation(line: &str) -> usize {
+        line.chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum()
+    }
+
+    /// Adds the node below the last node on the `depth`th level.
+    fn insert(nodes: &mut Vec<Node>, node: Node, depth: usize) {
+        match (depth, nodes.last_mut()) {
+            (0, _) | (_, None) => nodes.push(node),
+            (_, Some(last)) => insert(&mut last.children, node, depth - 1),
+        }
+    }
+
+    fn last_at(nodes: &mut [Node], depth: usize) -> Option<&mut Node> {
+        let last = nodes.last_mut()?;
+        if depth == 0 {
+            Some(last)
+        } else {
+            last_at(&mut last.children, depth - 1)
+        }
+    }
+
+    let mut nodes = vec![];
+    // The indentation of each item on the path to the last item.
+    let mut indents: Vec<usize> = vec![];
+
+    for line in s.lines() {
+        let indent = indentation(line);
+        let trimmed = line.trim();
+
+        let item = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet));
+
+        if let Some(item) = item {
+            while indents.last().is_some_and(|last| *last >= indent) {
+                indents.pop();
+            }
+
+            let (item, id) = match item.rfind("<!-- id:") {
+                Some(idx) => {
+                    let id = item[idx + 8..].trim_end_matches("-->").trim();
+                    (&item[..idx], uuid::Uuid::parse_str(id).ok())
+                }
+                None => (item, None),
+            };
+            let (text, assigned) = Node::parse_label(item);
+
+            insert(
+                &mut nodes,
+                Node {
+                    id,
+                    text,
+                    assigned,
+                    note: None,
+                    children: vec![],
+                },
+                indents.len(),
+            );
+            indents.push(indent);
+        } else if !indents.is_empty() {
+            let node = last_at(&mut nodes, indents.len() - 1).unwrap();
+            if trimmed.is_empty() && node.note.is_none() {
+                continue;
+            }
+            let note = node.note.get_or_insert_with(String::new);
+            note.push_str(unescape_note_line(trimmed));
+            note.push('\n');
+        }
+    }
+
+    fn trim_notes(nodes: &mut [Node]) {
+        for node in nodes {
+            if let Some(note) = &mut node.note {
+                *note = format!("{}\n", note.trim_end());
+            }
+            trim_notes(&mut node.children);
+        }
+    }
+    trim_notes(&mut nodes);
+
+    nodes
+}
+
+/// Undoes [`crate::export::outline::escape_note_line`].
+fn unescape_note_line(line: &str) -> &str {
+    match line.strip_prefix('\\') {
+        Some(rest) if rest.starts_with(['\\', '-', '*', '+']) => rest,
+        _ => line,
+    }
+}
+
+/// Parses the `outline` elements in the body of an OPML file. Notes are read from the
+/// `_note` attribute that most outliners use.
+pub fn parse_opml(s: &str) -> Result<Vec<Node>, String> {
+    fn recursive(element: roxmltree::Node) -> Vec<Node> {
+        element
+            .children()
+            .filter(|child| child.has_tag_name("outline"))
+            .map(|outline| {
+                let (text, assigned) =
+                    Node::parse_label(outline.attribute("text").unwrap_or_default());
+                Node {
+                    id: outline
+                        .attribute("actsId")
+                        .and_then(|id| uuid::Uuid::parse_str(id).ok()),
+                    text,
+                    assigned,
+                    note: outline
+                        .attribute("_note")
+                        .filter(|note| !note.trim().is_empty())
+                        .map(|note| format!("{}\n", note.trim_end())),
+                    children: recursive(outline),
+                }
+            })
+            .collect()
+    }
+
+    let doc = roxmltree::Document::parse(s).map_err(|e| e.to_string())?;
+    let body = doc
+        .descendants()
+        .find(|element| element.has_tag_name("body"))
+        .ok_or("OPML file has no body")?;
+
+    Ok(recursive(body))
+}
+
+/// Creates or updates the nodes below `parent`, or only reports what would change if
+/// `dry_run` is set.
+pub fn import(conn: &Conn, nodes: &[Node], parent: Option<ActID>, dry_run: bool) -> Summary {
+    // An activity can't be moved below itself, so the ids of the parent and its ancestors
+    // are treated as unknown, and so are ids already used further up in the outline, like
+    // those of a copy-pasted line.
+    let mut seen = HashSet::new();
+    let mut next = parent;
+    while let Some(id) = next {
+        seen.insert(id);
+        next = Activity::get_parent_index(conn, id);
+    }
+
+    fn recursive(
+        conn: &Conn,
+        nodes: &[Node],
+        parent: Option<ActID>,
+        seen: &mut HashSet<ActID>,
+        dry_run: bool,
+        summary: &mut Summary,
+    ) {
+        for node in nodes {
+            let existing = node
+                .id
+                .filter(|id| !seen.contains(id))
+                .and_then(|id| Activity::fetch_activity(conn, id).ok());
+
+            let id = match existing {
+                Some(mut old) => {
+                    let assigned = node.assigned.unwrap_or(old.assigned);
+                    if old.text != node.text || old.parent != parent || old.assigned != assigned {
+                        summary.activities_updated += 1;
+                        if !dry_run {
+                            if old.text != node.text {
+                                old.modify_text(node.text.clone(), conn).unwrap();
+                            }
+                            Activity::set_parent(conn, old.id, parent);
+                            crate::sql::set_assigned(conn, old.id, assigned);
+                        }
+                    }
+                    old.id
+                }
+                None => {
+                    let activity = Activity::new(node.text.clone(), parent);
+                    summary.activities_added += 1;
+                    if !dry_run {
+                        crate::sql::new_activity(conn, &activity).unwrap();
+                        if let Some(assigned) = node.assigned {
+                            crate::sql::set_assigned(conn, activity.id, assigned);
+                        }
+                    }
+                    activity.id
+                }
+            };
+            seen.insert(id);
+
+            if let Some(note) = &node.note {
+                if Some(note) != crate::notes::read(conn, id).as_ref() {
+                    summary.notes_written += 1;
+                    if !dry_run {
+                        crate::notes::write(conn, id, note);
+                    }
+                }
+            }
+
+            recursive(conn, &node.children, Some(id), seen, dry_run, summary);
+        }
+    }
+
+    let mut summary = Summary::default();
+    recursive(conn, nodes, parent, &mut seen, dry_run, &mut summary);
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::outline::{to_markdown, to_opml};
+
+    fn tree() -> Vec<Node> {
+        vec![Node {
+            id: None,
+            text: "Music".to_string(),
+            assigned: Some(40),
+            note: Some("Scales first.\n".to_string()),
+            children: vec![
+                Node {
+                    id: Some(uuid::Uuid::from_u128(1)),
+                    text: "Piano & keys".to_string(),
+                    assigned: Some(60),
+                    note: None,
+                    children: vec![],
+                },
+                Node {
+                    id: None,
+                    text: "Theory".to_string(),
+                    assigned: None,
+                    note: None,
+                    children: vec![],
+                },
+            ],
+        }]
+    }
+
+    #[test]
+    fn test_markdown_roundtrip() {
+        assert_eq!(parse_markdown(&to_markdown(&tree())), tree());
+    }
+
+    #[test]
+    fn test_opml_roundtrip() {
+        assert_eq!(parse_opml(&to_opml(&tree())).unwrap(), tree());
+    }
+
+    #[test]
+    fn test_note_with_a_list() {
+        let mut nodes = tree();
+        nodes[0].note = Some("Warm up:\n- scales\n* arpeggios\n\\- kept\n".to_string());
+        assert_eq!(parse_markdown(&to_markdown(&nodes)), nodes);
+    }
+
+    #[test]
+    fn test_import() {
+        let conn = crate::sql::init_in_memory();
+        import(&conn, &tree(), None, false);
+        let music = Activity::fetch_children(&conn, None).remove(0);
+        let kids = Activity::fetch_children(&conn, Some(music.id));
+        assert_eq!(music.assigned, 40);
+        assert_eq!(kids[0].assigned, 60);
+        assert_eq!(kids[1].assigned, 50);
+        assert_eq!(
+            crate::notes::read(&conn, music.id).unwrap(),
+            "Scales first.\n"
+        );
+
+        // Moving Piano to the root through its id puts it after Music.
+        crate::sql::execute(&conn, "UPDATE activities SET updated_at = 0").unwrap();
+        let piano = Node {
+            id: Some(kids[0].id),
+            text: "Keys".to_string(),
+            assigned: Some(30),
+            note: None,
+            children: vec![],
+        };
+        let summary = import(&conn, &[piano], None, false);
+        assert_eq!(summary.activities_updated, 1);
+        let roots = Activity::fetch_children(&conn, None);
+        assert_eq!(roots[1].id, kids[0].id);
+        assert_eq!(roots[1].text, "Keys");
+        assert_eq!(roots[1].assigned, 30);
+        assert!(roots[1].updated_at > 0);
+    }
+
+    #[test]
+    fn test_hand_written_markdown() {
+        let nodes = parse_markdown("* Work [70%]\n\t* Deep work\n* Rest [30%]\n");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].assigned, Some(70));
+        assert_eq!(nodes[0].children[0].text, "Deep work");
+        assert_eq!(nodes[1].text, "Rest");
+    }
+
+    #[test]
+    fn test_import_repeated_id() {
+        let conn = crate::sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        crate::sql::new_activity(&conn, &music).unwrap();
+
+        // A copy-pasted line that kept its id, nested below the original.
+        let node = |text: &str, children| Node {
+            id: Some(music.id),
+            text: text.to_string(),
+            assigned: None,
+            note: None,
+            children,
+        };
+        let nodes = vec![node("Music", vec![node("Music copy", vec![])])];
+
+        let summary = import(&conn, &nodes, None, false);
+        assert_eq!(summary.activities_added, 1);
+        assert_eq!(Activity::get_parent_index(&conn, music.id), None);
+        let copy = Activity::fetch_children(&conn, Some(music.id)).remove(0);
+        assert_ne!(copy.id, music.id);
+        assert_eq!(Activity::path(&conn, copy.id), "Music/Music copy");
+    }
+}
@@ -0,0 +1,130 @@
+//! Shared logic for importing time logs from other time trackers.
+//!
+//! Each format is parsed into [`Entry`]s, whose path is made up of the tracker's project
+//! followed by its tags. The path is resolved below a chosen parent, creating activities
+//! that don't exist yet. Sessions that were already imported are skipped, so importing the
+//! same file twice doesn't log anything the second time.
+
+use std::collections::HashSet;
+
+use super::Summary;
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::ActID;
+use crate::Conn;
+
+/// A tracked stretch of time in another time tracker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub path: Vec<String>,
+    /// Unix time the entry started.
+    pub start: u64,
+    /// Unix time the entry ended.
+    pub end: u64,
+}
+
+impl Entry {
+    /// Builds the path from a project and tags, leaving out empty names. An entry with
+    /// neither ends up in "Untagged".
+    pub fn new(project: Option<&str>, tags: &[String], start: u64, end: u64) -> Self {
+        let mut path: Vec<String> = project
+            .into_iter()
+            .map(|p| p.to_string())
+            .chain(tags.iter().cloned())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        if path.is_empty() {
+            path.push("Untagged".to_string());
+        }
+
+        Self { path, start, end }
+    }
+
+    pub fn minutes(&self) -> f64 {
+        self.end.saturating_sub(self.start) as f64 / 60.
+    }
+}
+
+/// Logs the entries below `parent`, or only reports what would change if `dry_run` is set.
+pub fn import(conn: &Conn, entries: &[Entry], parent: Option<ActID>, dry_run: bool) -> Summary {
+    let mut summary = Summary::default();
+    let mut new_paths: HashSet<Vec<String>> = HashSet::new();
+    // Nothing is logged in a dry run, so entries that repeat within the file are only
+    // skipped like the real import does with this.
+    let mut previewed: HashSet<(&[String], u64, u64)> = HashSet::new();
+
+    for entry in entries {
+        if entry.end <= entry.start {
+            continue;
+        }
+
+        if dry_run {
+            if !previewed.insert((&entry.path, entry.start, entry.end)) {
+                continue;
+            }
+            for depth in 1..=entry.path.len() {
+                let prefix = &entry.path[..depth];
+                if Activity::resolve_path(conn, parent, prefix, None).is_none() {
+                    new_paths.insert(prefix.to_vec());
+                }
+            }
+
+            let logged = Activity::resolve_path(conn, parent, &entry.path, None)
+                .is_some_and(|id| Session::exists(conn, id, entry.minutes(), entry.end));
+            if !logged {
+                summary.sessions_added += 1;
+            }
+            continue;
+        }
+
+        let id = Activity::resolve_path(
+            conn,
+            parent,
+            &entry.path,
+            Some(&mut summary.activities_added),
+        )
+        .unwrap();
+
+        if !Session::exists(conn, id, entry.minutes(), entry.end) {
+            Session::log(conn, id, entry.minutes(), entry.end);
+            summary.sessions_added += 1;
+        }
+    }
+
+    if dry_run {
+        summary.activities_added = new_paths.len();
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_twice() {
+        let conn = crate::sql::init_in_memory();
+        let entries = vec![
+            Entry::new(Some("work"), &["meeting".to_string()], 1000, 4600),
+            Entry::new(Some("work"), &[], 5000, 5600),
+            Entry::new(Some("work"), &[], 5000, 5600),
+        ];
+
+        let preview = import(&conn, &entries, None, true);
+        assert_eq!(preview.activities_added, 2);
+        assert_eq!(preview.sessions_added, 2);
+
+        assert_eq!(import(&conn, &entries, None, false), preview);
+        assert_eq!(import(&conn, &entries, None, false), Summary::default());
+
+        let work = Activity::resolve_path(&conn, None, &["work".to_string()], None).unwrap();
+        let minutes: f64 = Session::get_history(&conn, work)
+            .iter()
+            .map(|s| s.minutes())
+            .sum();
+        assert_eq!(minutes, 70.);
+    }
+}
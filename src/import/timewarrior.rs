@@ -0,0 +1,74 @@
+//! Parses the output of `timew export`.
+//!
+//! Timewarrior has no projects, so the tags alone make up the path, in the order they
+//! were given. Intervals that are still open are skipped.
+
+use serde::Deserialize;
+
+use super::timelog::Entry;
+
+#[derive(Deserialize)]
+struct Interval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Parses timestamps like `20230314T150926Z`.
+fn parse_time(s: &str) -> Option<u64> {
+    let s = s.strip_suffix('Z')?;
+    let (date, time) = s.split_once('T')?;
+    if date.len() != 8 || time.len() != 6 {
+        return None;
+    }
+
+    let num = |s: &str| s.parse::<u32>().ok();
+    let days = crate::utils::days_from_civil(
+        date[0..4].parse().ok()?,
+        num(&date[4..6])?,
+        num(&date[6..8])?,
+    );
+    let secs = num(&time[0..2])? * 3600 + num(&time[2..4])? * 60 + num(&time[4..6])?;
+
+    u64::try_from(days * 86400 + secs as i64).ok()
+}
+
+pub fn parse(s: &str) -> Result<Vec<Entry>, String> {
+    let intervals: Vec<Interval> = serde_json::from_str(s).map_err(|e| e.to_string())?;
+    let mut entries = vec![];
+
+    for interval in intervals {
+        let Some(end) = interval.end else {
+            continue;
+        };
+        let start =
+            parse_time(&interval.start).ok_or(format!("Invalid time: {}", interval.start))?;
+        let end = parse_time(&end).ok_or(format!("Invalid time: {}", end))?;
+        entries.push(Entry::new(None, &interval.tags, start, end));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let s = r#"[
+            {"id":2,"start":"20230314T000000Z","end":"20230314T003000Z","tags":["work","acts"]},
+            {"id":1,"start":"20230315T000000Z"}
+        ]"#;
+        let entries = parse(s).unwrap();
+        assert_eq!(
+            entries,
+            vec![Entry {
+                path: vec!["work".to_string(), "acts".to_string()],
+                start: 1678752000,
+                end: 1678752000 + 1800,
+            }]
+        );
+    }
+}
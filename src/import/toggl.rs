@@ -0,0 +1,80 @@
+//! Parses Toggl Track's detailed report CSV export.
+//!
+//! The path is made up of the project, the task if there is one, and then the tags. Toggl
+//! writes times in the account's time zone without saying which one, so they're read as UTC.
+
+use super::timelog::Entry;
+
+/// Parses a date like `2023-03-14` and a time like `15:09:26`.
+fn parse_time(date: &str, time: &str) -> Option<u64> {
+    let mut parts = time.trim().splitn(3, ':');
+    let hours = parts.next()?.parse::<u64>().ok()?;
+    let minutes = parts.next()?.parse::<u64>().ok()?;
+    let seconds = parts.next().unwrap_or("0").parse::<u64>().ok()?;
+
+    Some(crate::utils::parse_date(date)? + hours * 3600 + minutes * 60 + seconds)
+}
+
+pub fn parse(s: &str) -> Result<Vec<Entry>, String> {
+    let mut reader = csv::Reader::from_reader(s.as_bytes());
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let column = |name: &str| headers.iter().position(|header| header == name);
+
+    let required = |name: &str| column(name).ok_or(format!("Missing column: {}", name));
+    let project = required("Project")?;
+    let start_date = required("Start date")?;
+    let start_time = required("Start time")?;
+    let end_date = required("End date")?;
+    let end_time = required("End time")?;
+    let task = column("Task");
+    let tags = column("Tags");
+
+    let mut entries = vec![];
+
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        let field = |idx: usize| record.get(idx).unwrap_or_default();
+
+        let start = parse_time(field(start_date), field(start_time)).ok_or(format!(
+            "Invalid start: {} {}",
+            field(start_date),
+            field(start_time)
+        ))?;
+        let end = parse_time(field(end_date), field(end_time)).ok_or(format!(
+            "Invalid end: {} {}",
+            field(end_date),
+            field(end_time)
+        ))?;
+
+        let mut path: Vec<String> = task.map(|idx| field(idx).to_string()).into_iter().collect();
+        if let Some(idx) = tags {
+            path.extend(field(idx).split(',').map(|tag| tag.to_string()));
+        }
+
+        entries.push(Entry::new(Some(field(project)), &path, start, end));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let s = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+                 Tor,t@example.com,,Music,,scales,No,2023-03-14,00:00:00,2023-03-14,00:45:00,00:45:00,\"piano, theory\"\n";
+        let entries = parse(s).unwrap();
+        assert_eq!(
+            entries[0].path,
+            vec![
+                "Music".to_string(),
+                "piano".to_string(),
+                "theory".to_string()
+            ]
+        );
+        assert_eq!(entries[0].start, 1678752000);
+        assert_eq!(entries[0].minutes(), 45.);
+    }
+}
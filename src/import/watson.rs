@@ -0,0 +1,45 @@
+//! Parses Watson's `frames` file, found in Watson's config directory.
+//!
+//! Each frame is an array of `[start, stop, project, id, tags, updated_at]`. The project
+//! followed by the tags make up the path.
+
+use serde::Deserialize;
+
+use super::timelog::Entry;
+
+#[derive(Deserialize)]
+struct Frame {
+    start: u64,
+    stop: u64,
+    project: String,
+    _id: serde::de::IgnoredAny,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    _updated_at: Option<serde::de::IgnoredAny>,
+}
+
+pub fn parse(s: &str) -> Result<Vec<Entry>, String> {
+    let frames: Vec<Frame> = serde_json::from_str(s).map_err(|e| e.to_string())?;
+
+    Ok(frames
+        .into_iter()
+        .map(|frame| Entry::new(Some(&frame.project), &frame.tags, frame.start, frame.stop))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let s = r#"[[1678752000, 1678753800, "music", "f00", ["piano"], 1678753800]]"#;
+        let entries = parse(s).unwrap();
+        assert_eq!(
+            entries[0].path,
+            vec!["music".to_string(), "piano".to_string()]
+        );
+        assert_eq!(entries[0].minutes(), 30.);
+    }
+}
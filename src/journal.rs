@@ -0,0 +1,728 @@
+//! Undo and redo. Changes to the tree, the assignments and the sessions are wrapped in
+//! [`record`], which keeps what they did to the activities, notes, history, timer, slots
+//! and collapsed rows in the `journal` table. The last [`KEEP`] entries are kept, so
+//! undo works across restarts and between the front-ends.
+//!
+//! Undoing moves back through the entries, redoing forward again. Recording a new entry
+//! drops the ones that were undone.
+
+use serde::{Deserialize, Serialize};
+
+use crate::activity::Activity;
+use crate::sql;
+use crate::ActID;
+use crate::Conn;
+
+/// How many entries are kept.
+pub const KEEP: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Row {
+    text: String,
+    parent: Option<ActID>,
+    assigned: u32,
+    archived: bool,
+    #[serde(default)]
+    created_at: u64,
+    #[serde(default)]
+    updated_at: u64,
+    #[serde(default)]
+    position: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Change {
+    /// `None` where the activity doesn't exist.
+    Activity {
+        id: ActID,
+        before: Option<Row>,
+        after: Option<Row>,
+    },
+    Note {
+        id: ActID,
+        before: Option<String>,
+        after: Option<String>,
+    },
+    /// A history row that was added. Logging adds one to the activity and each ancestor.
+    Session {
+        id: ActID,
+        minutes: f64,
+        timestamp: u64,
+    },
+    RemovedSession {
+        id: ActID,
+        minutes: f64,
+        timestamp: u64,
+    },
+    /// The activity and start of the running timer, `None` where none was running.
+    Timer {
+        before: Option<(ActID, u64)>,
+        after: Option<(ActID, u64)>,
+    },
+    Slot {
+        id: uuid::Uuid,
+        before: Option<SlotRow>,
+        after: Option<SlotRow>,
+    },
+    Collapsed {
+        id: ActID,
+        before: bool,
+        after: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SlotRow {
+    activity: ActID,
+    start: u64,
+    length: u64,
+    title: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub label: String,
+    /// Unix time of when it was recorded.
+    pub timestamp: u64,
+    pub undone: bool,
+}
+
+/// The columns of an activity that are recorded, in the order of [`Row`]'s fields.
+const ROW_COLUMNS: [&str; 7] = [
+    "text",
+    "parent",
+    "assigned",
+    "archived",
+    "created_at",
+    "updated_at",
+    "position",
+];
+
+impl Row {
+    /// Reads the row from the [`ROW_COLUMNS`] starting at column `first`.
+    fn read(row: &rusqlite::Row, first: usize) -> rusqlite::Result<Self> {
+        Ok(Self {
+            text: row.get(first)?,
+            parent: row
+                .get::<usize, Option<String>>(first + 1)?
+                .map(|parent| uuid::Uuid::parse_str(&parent).unwrap()),
+            assigned: row.get(first + 2)?,
+            archived: row.get(first + 3)?,
+            created_at: row.get(first + 4)?,
+            updated_at: row.get(first + 5)?,
+            position: row.get(first + 6)?,
+        })
+    }
+}
+
+/// The triggers [`start`] creates, which [`stop`] drops again.
+const TRIGGERS: [&str; 16] = [
+    "touch_activity_insert",
+    "touch_activity_update",
+    "touch_activity_delete",
+    "touch_note_insert",
+    "touch_note_update",
+    "touch_note_delete",
+    "keep_added_sessions",
+    "keep_removed_sessions",
+    "touch_timer_insert",
+    "touch_timer_update",
+    "touch_timer_delete",
+    "touch_slot_insert",
+    "touch_slot_update",
+    "touch_slot_delete",
+    "touch_collapsed_insert",
+    "touch_collapsed_delete",
+];
+
+impl SlotRow {
+    /// Reads the activity, start, length and title starting at column `first`.
+    fn read(row: &rusqlite::Row, first: usize) -> rusqlite::Result<Self> {
+        Ok(Self {
+            activity: uuid::Uuid::parse_str(&row.get::<usize, String>(first)?).unwrap(),
+            start: row.get(first + 1)?,
+            length: row.get(first + 2)?,
+            title: row.get(first + 3)?,
+        })
+    }
+}
+
+/// Sets up the triggers that keep what an operation touches in temporary tables: the
+/// activities, notes, slots, collapsed rows and timer as they were before it first
+/// changed them, and the history rows it added and removed. Only those are compared
+/// afterwards, and history rows are caught even when their rowids are reused.
+fn start(conn: &Conn) {
+    let columns = ROW_COLUMNS.join(", ");
+    let old = ROW_COLUMNS
+        .map(|column| format!("old.{}", column))
+        .join(", ");
+    let changed = ROW_COLUMNS
+        .map(|column| format!("old.{0} IS NOT new.{0}", column))
+        .join(" OR ");
+    let statement = format!(
+        "CREATE TEMP TABLE IF NOT EXISTS touched_activities (id PRIMARY KEY, existed, {columns});
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_activity_insert AFTER INSERT ON main.activities
+        BEGIN
+            INSERT OR IGNORE INTO touched_activities (id, existed) VALUES (new.id, 0);
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_activity_update AFTER UPDATE ON main.activities
+        WHEN {changed}
+        BEGIN
+            INSERT OR IGNORE INTO touched_activities VALUES (old.id, 1, {old});
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_activity_delete AFTER DELETE ON main.activities
+        BEGIN
+            INSERT OR IGNORE INTO touched_activities VALUES (old.id, 1, {old});
+        END;
+
+        CREATE TEMP TABLE IF NOT EXISTS touched_notes (id PRIMARY KEY, existed, note);
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_note_insert AFTER INSERT ON main.notes
+        BEGIN
+            INSERT OR IGNORE INTO touched_notes VALUES (new.id, 0, NULL);
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_note_update AFTER UPDATE ON main.notes
+        WHEN old.note IS NOT new.note
+        BEGIN
+            INSERT OR IGNORE INTO touched_notes VALUES (old.id, 1, old.note);
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_note_delete AFTER DELETE ON main.notes
+        BEGIN
+            INSERT OR IGNORE INTO touched_notes VALUES (old.id, 1, old.note);
+        END;
+
+        CREATE TEMP TABLE IF NOT EXISTS added_sessions (id, duration, timestamp);
+        CREATE TEMP TRIGGER IF NOT EXISTS keep_added_sessions AFTER INSERT ON main.history
+        BEGIN
+            INSERT INTO added_sessions VALUES (new.id, new.duration, new.timestamp);
+        END;
+        CREATE TEMP TABLE IF NOT EXISTS removed_sessions (id, duration, timestamp);
+        CREATE TEMP TRIGGER IF NOT EXISTS keep_removed_sessions AFTER DELETE ON main.history
+        BEGIN
+            INSERT INTO removed_sessions VALUES (old.id, old.duration, old.timestamp);
+        END;
+
+        CREATE TEMP TABLE IF NOT EXISTS touched_timer (key PRIMARY KEY, id, started);
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_timer_insert BEFORE INSERT ON main.timer
+        BEGIN
+            INSERT OR IGNORE INTO touched_timer
+                SELECT 0, t.id, t.started FROM (SELECT 1) LEFT JOIN timer t;
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_timer_update BEFORE UPDATE ON main.timer
+        BEGIN
+            INSERT OR IGNORE INTO touched_timer
+                SELECT 0, t.id, t.started FROM (SELECT 1) LEFT JOIN timer t;
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_timer_delete BEFORE DELETE ON main.timer
+        BEGIN
+            INSERT OR IGNORE INTO touched_timer
+                SELECT 0, t.id, t.started FROM (SELECT 1) LEFT JOIN timer t;
+        END;
+
+        CREATE TEMP TABLE IF NOT EXISTS touched_slots
+            (id PRIMARY KEY, existed, activity, start, length, title);
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_slot_insert AFTER INSERT ON main.slots
+        BEGIN
+            INSERT OR IGNORE INTO touched_slots (id, existed) VALUES (new.id, 0);
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_slot_update AFTER UPDATE ON main.slots
+        BEGIN
+            INSERT OR IGNORE INTO touched_slots
+                VALUES (old.id, 1, old.activity, old.start, old.length, old.title);
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_slot_delete AFTER DELETE ON main.slots
+        BEGIN
+            INSERT OR IGNORE INTO touched_slots
+                VALUES (old.id, 1, old.activity, old.start, old.length, old.title);
+        END;
+
+        CREATE TEMP TABLE IF NOT EXISTS touched_collapsed (id PRIMARY KEY, existed);
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_collapsed_insert AFTER INSERT ON main.collapsed
+        BEGIN
+            INSERT OR IGNORE INTO touched_collapsed VALUES (new.id, 0);
+        END;
+        CREATE TEMP TRIGGER IF NOT EXISTS touch_collapsed_delete AFTER DELETE ON main.collapsed
+        BEGIN
+            INSERT OR IGNORE INTO touched_collapsed VALUES (old.id, 1);
+        END;
+
+        DELETE FROM touched_activities;
+        DELETE FROM touched_notes;
+        DELETE FROM added_sessions;
+        DELETE FROM removed_sessions;
+        DELETE FROM touched_timer;
+        DELETE FROM touched_slots;
+        DELETE FROM touched_collapsed;",
+    );
+    conn.execute_batch(&statement).unwrap();
+}
+
+/// Drops the triggers again, so writes outside of [`record`] aren't kept.
+fn stop(conn: &Conn) {
+    let statement = TRIGGERS
+        .map(|trigger| format!("DROP TRIGGER IF EXISTS temp.{};", trigger))
+        .join("\n");
+    conn.execute_batch(&statement).unwrap();
+}
+
+/// What changed since [`start`], going by what the triggers kept.
+fn changes(conn: &Conn) -> Vec<Change> {
+    let mut changes = vec![];
+
+    let after = ROW_COLUMNS.map(|column| format!("a.{}", column)).join(", ");
+    let before = ROW_COLUMNS.map(|column| format!("t.{}", column)).join(", ");
+    let statement = format!(
+        "SELECT t.id, t.existed, a.id IS NOT NULL, {before}, {after}
+            FROM temp.touched_activities t LEFT JOIN main.activities a ON a.id = t.id
+            ORDER BY t.rowid",
+    );
+    let first = 3 + ROW_COLUMNS.len();
+    for (id, before, after) in sql::query_map(conn, &statement, |row| {
+        Ok((
+            uuid::Uuid::parse_str(&row.get::<usize, String>(0)?).unwrap(),
+            row.get::<usize, bool>(1)?
+                .then(|| Row::read(row, 3))
+                .transpose()?,
+            row.get::<usize, bool>(2)?
+                .then(|| Row::read(row, first))
+                .transpose()?,
+        ))
+    })
+    .unwrap()
+    {
+        if before != after {
+            changes.push(Change::Activity { id, before, after });
+        }
+    }
+
+    let statement = "SELECT t.id, t.existed, t.note, n.note
+        FROM temp.touched_notes t LEFT JOIN main.notes n ON n.id = t.id
+        ORDER BY t.rowid";
+    for (id, before, after) in sql::query_map(conn, statement, |row| {
+        Ok((
+            uuid::Uuid::parse_str(&row.get::<usize, String>(0)?).unwrap(),
+            row.get::<usize, bool>(1)?
+                .then(|| row.get::<usize, String>(2))
+                .transpose()?,
+            row.get::<usize, Option<String>>(3)?,
+        ))
+    })
+    .unwrap()
+    {
+        if before != after {
+            changes.push(Change::Note { id, before, after });
+        }
+    }
+
+    let timer = |row: &rusqlite::Row, first: usize| -> rusqlite::Result<_> {
+        Ok(row
+            .get::<usize, Option<String>>(first)?
+            .map(|id| uuid::Uuid::parse_str(&id).unwrap())
+            .zip(row.get::<usize, Option<u64>>(first + 1)?))
+    };
+    let statement = "SELECT t.id, t.started, n.id, n.started
+        FROM temp.touched_timer t LEFT JOIN main.timer n";
+    for (before, after) in
+        sql::query_map(conn, statement, |row| Ok((timer(row, 0)?, timer(row, 2)?))).unwrap()
+    {
+        if before != after {
+            changes.push(Change::Timer { before, after });
+        }
+    }
+
+    let statement = "SELECT t.id, t.existed, s.id IS NOT NULL,
+            t.activity, t.start, t.length, t.title, s.activity, s.start, s.length, s.title
+        FROM temp.touched_slots t LEFT JOIN main.slots s ON s.id = t.id
+        ORDER BY t.rowid";
+    for (id, before, after) in sql::query_map(conn, statement, |row| {
+        Ok((
+            uuid::Uuid::parse_str(&row.get::<usize, String>(0)?).unwrap(),
+            row.get::<usize, bool>(1)?
+                .then(|| SlotRow::read(row, 3))
+                .transpose()?,
+            row.get::<usize, bool>(2)?
+                .then(|| SlotRow::read(row, 7))
+                .transpose()?,
+        ))
+    })
+    .unwrap()
+    {
+        if before != after {
+            changes.push(Change::Slot { id, before, after });
+        }
+    }
+
+    let statement = "SELECT t.id, t.existed, c.id IS NOT NULL
+        FROM temp.touched_collapsed t LEFT JOIN main.collapsed c ON c.id = t.id
+        ORDER BY t.rowid";
+    for (id, before, after) in sql::query_map(conn, statement, |row| {
+        Ok((
+            uuid::Uuid::parse_str(&row.get::<usize, String>(0)?).unwrap(),
+            row.get::<usize, bool>(1)?,
+            row.get::<usize, bool>(2)?,
+        ))
+    })
+    .unwrap()
+    {
+        if before != after {
+            changes.push(Change::Collapsed { id, before, after });
+        }
+    }
+
+    let session = |row: &rusqlite::Row| {
+        Ok((
+            uuid::Uuid::parse_str(&row.get::<usize, String>(0)?).unwrap(),
+            row.get::<usize, f64>(1)?,
+            row.get::<usize, u64>(2)?,
+        ))
+    };
+    let statement = "SELECT id, duration, timestamp FROM temp.added_sessions";
+    changes.extend(
+        sql::query_map(conn, statement, session)
+            .unwrap()
+            .into_iter()
+            .map(|(id, minutes, timestamp)| Change::Session {
+                id,
+                minutes,
+                timestamp,
+            }),
+    );
+    let statement = "SELECT id, duration, timestamp FROM temp.removed_sessions";
+    changes.extend(
+        sql::query_map(conn, statement, session)
+            .unwrap()
+            .into_iter()
+            .map(|(id, minutes, timestamp)| Change::RemovedSession {
+                id,
+                minutes,
+                timestamp,
+            }),
+    );
+
+    changes
+}
+
+/// Runs `f` and records what it changed so it can be undone. Nothing is recorded if
+/// nothing changed.
+pub fn record<T>(conn: &Conn, label: &str, f: impl FnOnce() -> T) -> T {
+    start(conn);
+    let result = f();
+    let changes = changes(conn);
+    stop(conn);
+    if changes.is_empty() {
+        return result;
+    }
+
+    sql::execute(conn, "DELETE FROM journal WHERE undone = 1").unwrap();
+    conn.execute(
+        "INSERT INTO journal (label, changes, timestamp) VALUES (?1, ?2, ?3)",
+        rusqlite::params![
+            label,
+            serde_json::to_string(&changes).unwrap(),
+            crate::utils::current_unix().as_secs()
+        ],
+    )
+    .unwrap();
+    let statement = format!(
+        "DELETE FROM journal WHERE seq <= (SELECT MAX(seq) FROM journal) - {}",
+        KEEP
+    );
+    sql::execute(conn, &statement).unwrap();
+
+    result
+}
+
+/// Undoes the last entry that isn't undone, returning its label.
+pub fn undo(conn: &Conn) -> Option<String> {
+    step(conn, "undone = 0 ORDER BY seq DESC", true)
+}
+
+/// Redoes the first entry that was undone, returning its label.
+pub fn redo(conn: &Conn) -> Option<String> {
+    step(conn, "undone = 1 ORDER BY seq ASC", false)
+}
+
+fn step(conn: &Conn, filter: &str, undo: bool) -> Option<String> {
+    let statement = format!(
+        "SELECT seq, label, changes FROM journal WHERE {} LIMIT 1",
+        filter
+    );
+    let (seq, label, changes) = sql::query_row(conn, &statement, |row| {
+        Ok((
+            row.get::<usize, i64>(0)?,
+            row.get::<usize, String>(1)?,
+            row.get::<usize, String>(2)?,
+        ))
+    })
+    .ok()?;
+    let mut changes: Vec<Change> = serde_json::from_str(&changes).unwrap();
+    if undo {
+        changes.reverse();
+    }
+
+    let tx = conn.unchecked_transaction().unwrap();
+    for change in changes {
+        apply(conn, change, undo);
+    }
+    let statement = format!(
+        "UPDATE journal SET undone = {} WHERE seq = {}",
+        undo as u8, seq
+    );
+    sql::execute(conn, &statement).unwrap();
+    tx.commit().unwrap();
+
+    Some(label)
+}
+
+fn apply(conn: &Conn, change: Change, undo: bool) {
+    match change {
+        Change::Activity { id, before, after } => {
+            let row = if undo { before } else { after };
+            let Some(row) = row else {
+                sql::delete_activity(conn, id);
+                return;
+            };
+            let statement = if Activity::fetch_activity(conn, id).is_ok() {
+                "UPDATE activities SET text = ?2, parent = ?3, assigned = ?4, archived = ?5,
+                    created_at = ?6, updated_at = ?7, position = ?8 WHERE id = ?1"
+            } else {
+                "INSERT INTO activities (id, text, parent, assigned, archived, created_at,
+                    updated_at, position) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+            };
+            conn.execute(
+                statement,
+                rusqlite::params![
+                    id.to_string(),
+                    row.text,
+                    row.parent.map(|p| p.to_string()),
+                    row.assigned,
+                    row.archived,
+                    row.created_at,
+                    row.updated_at,
+                    row.position
+                ],
+            )
+            .unwrap();
+        }
+        Change::Note { id, before, after } => {
+            let note = if undo { before } else { after };
+            crate::notes::write(conn, id, &note.unwrap_or_default());
+        }
+        Change::Session {
+            id,
+            minutes,
+            timestamp,
+        } => set_session(conn, id, minutes, timestamp, !undo),
+        Change::RemovedSession {
+            id,
+            minutes,
+            timestamp,
+        } => set_session(conn, id, minutes, timestamp, undo),
+        Change::Timer { before, after } => {
+            sql::execute(conn, "DELETE FROM timer").unwrap();
+            if let Some((id, started)) = if undo { before } else { after } {
+                conn.execute(
+                    "INSERT INTO timer (id, started) VALUES (?1, ?2)",
+                    rusqlite::params![id.to_string(), started],
+                )
+                .unwrap();
+            }
+        }
+        Change::Slot { id, before, after } => {
+            conn.execute("DELETE FROM slots WHERE id = ?1", [id.to_string()])
+                .unwrap();
+            if let Some(row) = if undo { before } else { after } {
+                conn.execute(
+                    "INSERT INTO slots (id, activity, start, length, title)
+                        VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![
+                        id.to_string(),
+                        row.activity.to_string(),
+                        row.start,
+                        row.length,
+                        row.title
+                    ],
+                )
+                .unwrap();
+            }
+        }
+        Change::Collapsed { id, before, after } => {
+            sql::set_collapsed(conn, id, if undo { before } else { after })
+        }
+    }
+}
+
+/// Adds the history row, or removes one that matches it.
+fn set_session(conn: &Conn, id: ActID, minutes: f64, timestamp: u64, add: bool) {
+    let statement = if add {
+        "INSERT INTO history (id, duration, timestamp) VALUES (?1, ?2, ?3)"
+    } else {
+        "DELETE FROM history WHERE rowid = (SELECT rowid FROM history
+            WHERE id = ?1 AND duration = ?2 AND timestamp = ?3 LIMIT 1)"
+    };
+    conn.execute(
+        statement,
+        rusqlite::params![id.to_string(), minutes, timestamp],
+    )
+    .unwrap();
+}
+
+/// The kept entries, the most recent first.
+pub fn entries(conn: &Conn) -> Vec<Entry> {
+    sql::query_map(
+        conn,
+        "SELECT label, timestamp, undone FROM journal ORDER BY seq DESC",
+        |row| {
+            Ok(Entry {
+                label: row.get(0)?,
+                timestamp: row.get(1)?,
+                undone: row.get(2)?,
+            })
+        },
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Session;
+
+    #[test]
+    fn test_undo_redo() {
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        let piano = Activity::new("Piano".to_string(), Some(music.id));
+        record(&conn, "Add", || {
+            sql::new_activity(&conn, &music).unwrap();
+            sql::new_activity(&conn, &piano).unwrap();
+        });
+        record(&conn, "Log", || Session::log(&conn, piano.id, 30., 1000));
+        crate::notes::write(&conn, piano.id, "Scales");
+        record(&conn, "Delete", || {
+            Activity::delete_activity(&conn, piano.id)
+        });
+        record(&conn, "Nothing", || {});
+        assert_eq!(entries(&conn).len(), 3);
+
+        assert_eq!(undo(&conn).unwrap(), "Delete");
+        assert_eq!(Activity::path(&conn, piano.id), "Music/Piano");
+        assert_eq!(crate::notes::read(&conn, piano.id).unwrap(), "Scales");
+        assert_eq!(undo(&conn).unwrap(), "Log");
+        assert!(Session::get_history(&conn, music.id).is_empty());
+
+        assert_eq!(redo(&conn).unwrap(), "Log");
+        assert_eq!(Session::get_history(&conn, music.id).len(), 1);
+        record(&conn, "Rename", || {
+            Activity::update_text(&conn, music.id, &"Songs".to_string()).unwrap()
+        });
+        assert!(redo(&conn).is_none());
+
+        assert_eq!(undo(&conn).unwrap(), "Rename");
+        assert_eq!(Activity::path(&conn, piano.id), "Music/Piano");
+        undo(&conn);
+        undo(&conn);
+        assert!(undo(&conn).is_none());
+        assert!(Activity::fetch_all_activities_flat(&conn).is_empty());
+    }
+
+    #[test]
+    fn test_undo_timer_slots_and_collapsed() {
+        use crate::slots::Slot;
+        use crate::timer::Timer;
+
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        let piano = Activity::new("Piano".to_string(), Some(music.id));
+        sql::new_activity(&conn, &music).unwrap();
+        sql::new_activity(&conn, &piano).unwrap();
+        let slot = Slot::new(
+            piano.id,
+            1000,
+            std::time::Duration::from_secs(3600),
+            String::new(),
+        );
+        slot.add(&conn);
+        sql::set_collapsed(&conn, piano.id, true);
+        conn.execute(
+            "INSERT INTO timer (id, started) VALUES (?1, 1000)",
+            [music.id.to_string()],
+        )
+        .unwrap();
+
+        record(&conn, "Start", || Timer::start(&conn, piano.id));
+        assert_eq!(Timer::running(&conn).unwrap().id, piano.id);
+        record(&conn, "Delete", || {
+            Activity::delete_activity(&conn, piano.id)
+        });
+        assert!(Slot::fetch_all(&conn).is_empty());
+
+        assert_eq!(undo(&conn).unwrap(), "Delete");
+        assert_eq!(Slot::fetch_all(&conn), vec![slot]);
+        assert!(sql::fetch_collapsed(&conn).contains(&piano.id));
+        assert_eq!(Timer::running(&conn).unwrap().id, piano.id);
+        assert_eq!(undo(&conn).unwrap(), "Start");
+        let timer = Timer::running(&conn).unwrap();
+        assert_eq!((timer.id, timer.started), (music.id, 1000));
+        assert!(Session::get_history(&conn, music.id).is_empty());
+
+        // 7 seconds in minutes only reads back the same from the journal with
+        // `float_roundtrip`, otherwise the row isn't found.
+        record(&conn, "Log", || {
+            Session::log(&conn, music.id, 7. / 60., 1000)
+        });
+        undo(&conn);
+        assert!(Session::get_history(&conn, music.id).is_empty());
+
+        // Nothing is kept outside of `record`.
+        let triggers: i64 = sql::query_row(
+            &conn,
+            "SELECT COUNT(*) FROM temp.sqlite_master WHERE type = 'trigger'",
+            |row| row.get(0),
+        )
+        .unwrap();
+        assert_eq!(triggers, 0);
+    }
+
+    #[test]
+    fn test_undo_split() {
+        use crate::split::{split, Distribution, Part};
+
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        sql::new_activity(&conn, &music).unwrap();
+        Session::log(&conn, music.id, 60., 1000);
+
+        // Unlogging the newest row and logging again reuses its rowid.
+        let parts = vec![
+            Part {
+                name: "Piano".to_string(),
+                assigned: 50,
+            },
+            Part {
+                name: "Theory".to_string(),
+                assigned: 50,
+            },
+        ];
+        let kids = record(&conn, "Split", || {
+            split(
+                &conn,
+                music.id,
+                &parts,
+                &Distribution::Percent(vec![50, 50]),
+            )
+            .unwrap()
+        });
+        assert_eq!(Session::get_history(&conn, kids[0]).len(), 1);
+
+        assert_eq!(undo(&conn).unwrap(), "Split");
+        assert!(Activity::fetch_children(&conn, Some(music.id)).is_empty());
+        let rows: i64 =
+            sql::query_row(&conn, "SELECT COUNT(*) FROM history", |row| row.get(0)).unwrap();
+        assert_eq!(rows, 1);
+        assert_eq!(Session::fetch_direct_sessions_of(&conn, music.id).len(), 1);
+
+        assert_eq!(redo(&conn).unwrap(), "Split");
+        assert_eq!(Session::get_history(&conn, kids[1]).len(), 1);
+        assert!(Session::fetch_direct_sessions_of(&conn, music.id).is_empty());
+    }
+}
@@ -1,12 +1,15 @@
 use crate::pages::treeview::TreeView;
+use clap::Parser;
 use iced::widget::{button, row};
 use pages::assignments::Assignments;
 use std::rc::Rc;
 
+use crate::pages::export::Export;
+use crate::pages::import::Import;
 use crate::pages::new_activity::NewActivity;
 
 use iced::widget::Column;
-use iced::{executor, Alignment, Application, Command, Element, Settings};
+use iced::{executor, Alignment, Application, Command, Element, Settings, Subscription};
 use pages::picker::Picker;
 
 pub fn main() -> iced::Result {
@@ -31,15 +34,54 @@ pub fn main() -> iced::Result {
     ));
     */
 
+    let cli = cli::Cli::parse();
+    match cli.overrides() {
+        Ok(overrides) => config::init(overrides),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
+    }
+    let profile = profile::active();
+    if !profile::list().iter().any(|p| p.name == profile) {
+        eprintln!(
+            "error: No profile named '{}', create it with `acts profile create {}`",
+            profile, profile
+        );
+        std::process::exit(2);
+    }
+    if let Some(command) = cli.command {
+        let conn = sql::init();
+        std::process::exit(cli::run(&conn, command, cli.json));
+    }
+
     App::run(Settings::default())
 }
 
 mod activity;
+mod cli;
+mod config;
+mod export;
 mod history;
+mod import;
+mod journal;
+mod merge;
+mod notes;
 mod pages;
+mod picker;
 //mod plan;
+mod profile;
+mod report;
+mod search;
+mod server;
+mod slots;
+mod split;
 mod sql;
+mod status;
+mod timer;
+mod tui;
 mod utils;
+mod vault;
 
 use crate::activity::Activity;
 use crate::pages::editpage::EditPage;
@@ -53,64 +95,81 @@ type ActID = Uuid;
 pub struct App {
     conn: Conn,
     pages: Vec<Box<dyn Page>>,
+    /// What was last undone or redone, shown on the main view.
+    status: String,
+    /// What the main view's list is ordered by.
+    sort: activity::SortKey,
 }
 
 impl App {
     fn view_activities(&self) -> Vec<Element<'static, Message>> {
-        let acts = Self::view_by_priority(self);
+        let acts = Activity::fetch_sorted(&self.conn, self.sort);
 
         let mut wtf = vec![];
 
         for act in acts {
             let button: iced::widget::button::Button<Message> =
-                iced::widget::button(iced::widget::text::Text::new(act.display_flat(&self.conn)))
-                    .on_press(Message::MainMessage(MainMessage::NewEdit(act.id)));
+                iced::widget::button(iced::widget::text::Text::new(format!(
+                    "{}{}",
+                    act.display_flat(&self.conn),
+                    notes::marker(&self.conn, act.id)
+                )))
+                .on_press(Message::MainMessage(MainMessage::NewEdit(act.id)));
             let row = iced::Element::new(iced::widget::row![button]);
             wtf.push(row);
         }
         wtf
     }
 
-    fn view_by_priority(&self) -> Vec<Activity> {
-        let mut activities = Activity::fetch_all_activities_flat(&self.conn)
-            .into_iter()
-            .filter(|act| Activity::fetch_children(&self.conn, Some(act.id)).is_empty())
-            .collect();
-        crate::Activity::assign_priorities(&self.conn, &mut activities);
-
-        fn recursive(leaves: &mut Vec<Activity>, activity: &mut Activity) {
-            if activity.children.is_empty() {
-                leaves.push(activity.clone());
-            } else {
-                for child in activity.children.iter_mut() {
-                    recursive(leaves, child);
-                }
-            }
-        }
-        let mut leaves = vec![];
-
-        for activity in activities.iter_mut() {
-            if activity.children.is_empty() {
-                leaves.push(activity.clone());
+    fn view_sort(&self) -> Element<'static, Message> {
+        let mut sort_row = row![iced::widget::text::Text::new("Sort by")].spacing(10);
+        for key in activity::SortKey::ALL {
+            let label = if key == self.sort {
+                format!("[{}]", key.label())
             } else {
-                for child in activity.children.iter_mut() {
-                    recursive(&mut leaves, child);
-                }
-            }
+                key.label().to_string()
+            };
+            sort_row = sort_row.push(
+                button(iced::widget::text::Text::new(label))
+                    .on_press(MainMessage::SortBy(key).into_message()),
+            );
         }
-
-        leaves.sort_by_key(|leaf| std::cmp::Reverse((leaf.priority * 1000.) as u64));
-        leaves
+        sort_row.into()
     }
 
     fn main_view(&self) -> Element<'static, Message> {
         let new_activity_button = button("Add activity")
             .on_press(MainMessage::PageAddActivity { parent: None }.into_message());
-        //   let refresh_button = button("Refresh").on_press(MainMessage::Refresh.into_message());
+        let refresh_button = button("Refresh").on_press(MainMessage::Refresh.into_message());
         let treeview_button = button("view tree").on_press(MainMessage::NewTreeView.into_message());
+        let export_button =
+            button("Export").on_press(MainMessage::NewExport { subtree: None }.into_message());
+        let import_button =
+            button("Import").on_press(MainMessage::NewImport { parent: None }.into_message());
+        let settings_button = button("Settings").on_press(MainMessage::NewSettings.into_message());
+        let profiles_button = button("Profiles").on_press(MainMessage::NewProfiles.into_message());
+        let search_button = button("Search").on_press(MainMessage::NewSearch.into_message());
+        let archive_button = button("Archive").on_press(MainMessage::NewArchive.into_message());
+        let undo_button = button("Undo").on_press(MainMessage::Undo.into_message());
+        let redo_button = button("Redo").on_press(MainMessage::Redo.into_message());
 
         iced::widget::column![
-            row![new_activity_button, treeview_button].padding(10),
+            row![
+                new_activity_button,
+                treeview_button,
+                search_button,
+                archive_button,
+                export_button,
+                import_button,
+                settings_button,
+                profiles_button,
+                refresh_button,
+                undo_button,
+                redo_button
+            ]
+            .padding(10),
+            iced::widget::text::Text::new(self.status.clone()),
+            self.view_sort(),
             Column::with_children(self.view_activities())
         ]
         .padding(20)
@@ -121,6 +180,45 @@ impl App {
     fn refresh(&mut self) {
         Activity::normalize_assignments(&self.conn);
     }
+
+    /// Ctrl+Z undoes, Ctrl+Y or Ctrl+Shift+Z redoes.
+    fn shortcut(event: iced::Event, status: iced::event::Status) -> Option<Message> {
+        use iced::keyboard::{Event, KeyCode};
+        match (event, status) {
+            (
+                iced::Event::Keyboard(Event::KeyPressed {
+                    key_code,
+                    modifiers,
+                }),
+                iced::event::Status::Ignored,
+            ) if modifiers.control() => match key_code {
+                KeyCode::Z if modifiers.shift() => Some(MainMessage::Redo.into_message()),
+                KeyCode::Z => Some(MainMessage::Undo.into_message()),
+                KeyCode::Y => Some(MainMessage::Redo.into_message()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The up and down arrow keys, for pages that select with them.
+    fn arrow_keys(event: iced::Event, status: iced::event::Status) -> Option<Message> {
+        use iced::keyboard::{Event, KeyCode};
+        match (event, status) {
+            (
+                iced::Event::Keyboard(Event::KeyPressed {
+                    key_code,
+                    modifiers,
+                }),
+                iced::event::Status::Ignored,
+            ) if modifiers.is_empty() => match key_code {
+                KeyCode::Up => Some(PageMessage::MoveSelection(-1).into_message()),
+                KeyCode::Down => Some(PageMessage::MoveSelection(1).into_message()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
 }
 
 /// Messages that are handled in main.rs
@@ -128,15 +226,70 @@ impl App {
 pub enum MainMessage {
     GoBack,
     Refresh,
-    DeleteActivity(ActID),
-    AddActivity { name: String, parent: Option<ActID> },
-    PageAddActivity { parent: Option<ActID> },
+    NewDelete(ActID),
+    NewMerge(ActID),
+    MergeActivity {
+        source: ActID,
+        target: ActID,
+    },
+    NewSplit(ActID),
+    SplitActivity {
+        id: ActID,
+        parts: Vec<split::Part>,
+        distribution: split::Distribution,
+    },
+    DeleteActivity {
+        id: ActID,
+        mode: activity::DeleteMode,
+    },
+    ArchiveActivity(ActID),
+    RenameActivity {
+        id: ActID,
+        text: String,
+    },
+    LogSession {
+        id: ActID,
+        minutes: f64,
+    },
+    SetAssigned(Vec<(ActID, u32)>),
+    Undo,
+    Redo,
+    AddActivity {
+        name: String,
+        parent: Option<ActID>,
+    },
+    PageAddActivity {
+        parent: Option<ActID>,
+    },
     NewTreeView,
     NewAssign(ActID),
     NewEdit(ActID),
-    ChooseParent { child: ActID },
-    SetParent { child: ActID, parent: Option<ActID> },
-    EditNote{id: ActID},
+    ChooseParent {
+        child: ActID,
+    },
+    SetParent {
+        child: ActID,
+        parent: Option<ActID>,
+    },
+    Reorder {
+        id: ActID,
+        to: activity::Reorder,
+    },
+    EditNote {
+        id: ActID,
+    },
+    NewExport {
+        subtree: Option<ActID>,
+    },
+    NewImport {
+        parent: Option<ActID>,
+    },
+    NewSettings,
+    NewProfiles,
+    NewSearch,
+    NewArchive,
+    SortBy(activity::SortKey),
+    SwitchProfile(String),
     NoOp,
 }
 
@@ -152,13 +305,37 @@ pub enum PageMessage {
     ValueSubmit,
     ValueGetInput(String),
     Adjust,
+    Export(export::Format),
+    Import {
+        mode: import::Mode,
+        dry_run: bool,
+    },
+    ImportFormat(import::Format),
+    SetTheme(config::Theme),
+    Note(pages::editpage::NoteMessage),
+    RenameProfile(String),
+    DeleteProfile(String),
+    MergeSessions(bool),
+    RestoreActivity(ActID),
+    PurgeActivity(ActID),
+    AddPart,
+    SplitBySession(bool),
+    /// The session at the index goes to the part at the index, or stays with `None`.
+    SessionPart(usize, Option<usize>),
+    ToggleCollapsed(ActID),
+    /// Collapses every activity that has children, or expands them all.
+    SetAllCollapsed(bool),
+    /// Moves the selection up or down by the number of rows, from the arrow keys.
+    MoveSelection(isize),
+    /// A slider moved the value at the index, and the others make room for it.
+    Slide(usize, u32),
+    Lock(usize, bool),
 }
 
 pub trait IntoMessage {
     fn into_message(self) -> Message;
 }
 
-
 impl IntoMessage for MainMessage {
     fn into_message(self) -> Message {
         Message::MainMessage(self)
@@ -185,44 +362,31 @@ impl Application for App {
 
     fn new(_flags: ()) -> (Self, Command<Message>) {
         let conn = sql::init();
+        vault::sync(&conn);
         let app = Self {
             conn,
             pages: vec![],
+            status: String::new(),
+            sort: activity::SortKey::default(),
         };
         (app, Command::none())
     }
 
     fn title(&self) -> String {
-        String::from("Counter - Iced")
+        format!("acts - {}", profile::active())
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
         self.refresh();
+        let is_action = matches!(message, Message::MainMessage(_));
         match message {
             Message::MainMessage(mainmsg) => match mainmsg {
-                MainMessage::EditNote{id} => {
-                        let mut file_path = std::path::PathBuf::new();
-
-    if let Some(home_dir) = dirs::home_dir() {
-        file_path.push(home_dir);
-        file_path.push(".local/share/acts/notes");
-    } 
-    // Create the acts directory if it doesn't exist
-    std::fs::create_dir_all(&file_path).expect("Failed to create acts directory");
-
-    file_path.push(id.to_string());
-
-    // Create the file if it doesn't exist
-     let _ = std::fs::OpenOptions::new().write(true).create_new(true).open(&file_path);
-
-    // Open the file with gedit directly
-    if cfg!(target_os = "linux") {
-         let _ = std::process::Command::new("gedit")
-            .arg(file_path)
-            .spawn();
-    }
+                MainMessage::EditNote { id } => {
+                    if let Err(e) = notes::open_in_editor(&self.conn, id) {
+                        eprintln!("{}", e);
+                    }
                 }
-                
+
                 MainMessage::PageAddActivity { parent } => {
                     self.pages.push(Box::new(NewActivity::new(parent)));
                 }
@@ -237,33 +401,177 @@ impl Application for App {
                 MainMessage::SetParent { child, parent } => {
                     // safe unwrap as || lazily evaluates from left to right.
                     if parent.is_none() || parent.unwrap() != child {
-                        Activity::set_parent(&self.conn, child, parent);
+                        let label = format!("Move {}", Activity::path(&self.conn, child));
+                        journal::record(&self.conn, &label, || {
+                            Activity::set_parent(&self.conn, child, parent)
+                        });
                     }
                     self.pages.pop();
                 }
 
+                MainMessage::Reorder { id, to } => {
+                    let label = format!("Move {} {}", Activity::path(&self.conn, id), to.label());
+                    journal::record(&self.conn, &label, || Activity::reorder(&self.conn, id, to));
+                }
+
                 MainMessage::NewAssign(id) => {
                     let parent = Activity::get_parent(&self.conn, id).map(|act| act.id);
                     self.pages
-                        .push(Box::new(Assignments::new(self.conn.clone(), parent)));
+                        .push(Box::new(Assignments::new(&self.conn, parent)));
+                }
+                MainMessage::NewExport { subtree } => {
+                    self.pages
+                        .push(Box::new(Export::new(self.conn.clone(), subtree)));
+                }
+                MainMessage::NewImport { parent } => {
+                    self.pages
+                        .push(Box::new(Import::new(self.conn.clone(), parent)));
+                }
+                MainMessage::NewSettings => {
+                    self.pages.push(Box::new(pages::settings::Settings::new()));
+                }
+                MainMessage::NewSearch => {
+                    self.pages
+                        .push(Box::new(pages::search::Search::new(self.conn.clone())));
+                    return iced::widget::text_input::focus(iced::widget::text_input::Id::new(
+                        "search",
+                    ));
+                }
+                MainMessage::NewArchive => {
+                    self.pages
+                        .push(Box::new(pages::archive::Archive::new(self.conn.clone())));
+                }
+                MainMessage::NewProfiles => {
+                    self.pages
+                        .push(Box::new(pages::profiles::Profiles::default()));
+                }
+                MainMessage::SwitchProfile(name) => {
+                    if profile::switch(&name).is_ok() {
+                        self.conn = sql::init();
+                        self.pages.clear();
+                        self.refresh();
+                    }
                 }
                 MainMessage::NewTreeView => {
                     self.pages.push(Box::new(TreeView::new(self.conn.clone())));
                 }
                 MainMessage::Refresh => self.refresh(),
-                MainMessage::DeleteActivity(id) => {
-                    Activity::delete_activity(&self.conn, id);
+                MainMessage::SortBy(key) => self.sort = key,
+                MainMessage::NewDelete(id) => {
+                    self.pages
+                        .push(Box::new(pages::delete::DeleteDialog::new(&self.conn, id)));
+                }
+                MainMessage::NewMerge(id) => {
+                    self.pages
+                        .push(Box::new(pages::merge::Merge::new(self.conn.clone(), id)));
+                }
+                MainMessage::MergeActivity { source, target } => {
+                    let label = format!(
+                        "Merge {} into {}",
+                        Activity::path(&self.conn, source),
+                        Activity::path(&self.conn, target)
+                    );
+                    let result = journal::record(&self.conn, &label, || {
+                        merge::merge(&self.conn, source, target)
+                    });
+                    match result {
+                        Ok(()) => {
+                            // The merge page, and the source's edit page.
+                            self.pages.pop();
+                            self.pages.pop();
+                        }
+                        Err(e) => self.status = e,
+                    }
+                }
+                MainMessage::NewSplit(id) => {
+                    self.pages
+                        .push(Box::new(pages::split::Split::new(&self.conn, id)));
+                }
+                MainMessage::SplitActivity {
+                    id,
+                    parts,
+                    distribution,
+                } => {
+                    let label = split::label(&self.conn, id, &parts);
+                    let result = journal::record(&self.conn, &label, || {
+                        split::split(&self.conn, id, &parts, &distribution)
+                    });
+                    match result {
+                        Ok(_) => {
+                            self.pages.pop();
+                        }
+                        Err(e) => self.status = e,
+                    }
+                }
+                MainMessage::DeleteActivity { id, mode } => {
+                    let label = format!("Delete {}", Activity::path(&self.conn, id));
+                    journal::record(&self.conn, &label, || {
+                        Activity::delete(&self.conn, id, mode)
+                    });
+                    // The dialog, and the edit page it was opened from.
+                    self.pages.pop();
+                    self.pages.pop();
+                    self.refresh();
+                }
+                MainMessage::ArchiveActivity(id) => {
+                    let label = format!("Archive {}", Activity::path(&self.conn, id));
+                    journal::record(&self.conn, &label, || Activity::archive(&self.conn, id));
+                    self.pages.pop();
+                    self.refresh();
+                }
+                MainMessage::RenameActivity { id, text } => {
+                    let label = format!("Rename {} to {}", Activity::path(&self.conn, id), text);
+                    journal::record(&self.conn, &label, || {
+                        Activity::update_text(&self.conn, id, &text).unwrap()
+                    });
+                    self.pages.pop();
+                }
+                MainMessage::LogSession { id, minutes } => {
+                    let label = format!("Log {}m on {}", minutes, Activity::path(&self.conn, id));
+                    let timestamp = utils::current_unix().as_secs();
+                    journal::record(&self.conn, &label, || {
+                        history::Session::log(&self.conn, id, minutes, timestamp)
+                    });
+                    self.pages.pop();
+                }
+                MainMessage::SetAssigned(values) => {
+                    journal::record(&self.conn, "Change assignments", || {
+                        for (id, assigned) in values {
+                            sql::set_assigned(&self.conn, id, assigned);
+                        }
+                    });
                     self.pages.pop();
+                }
+                MainMessage::Undo => {
+                    self.status = match journal::undo(&self.conn) {
+                        Some(label) => format!("Undid: {}", label),
+                        None => "Nothing to undo".to_string(),
+                    };
+                    // The pages could be showing activities that are gone now.
+                    self.pages.clear();
+                    self.refresh();
+                }
+                MainMessage::Redo => {
+                    self.status = match journal::redo(&self.conn) {
+                        Some(label) => format!("Redid: {}", label),
+                        None => "Nothing to redo".to_string(),
+                    };
+                    self.pages.clear();
                     self.refresh();
                 }
                 MainMessage::AddActivity { name, parent } => {
                     let activity = Activity::new(name, parent);
-                    sql::new_activity(&self.conn, &activity).unwrap();
+                    journal::record(&self.conn, &format!("Add {}", activity.text), || {
+                        sql::new_activity(&self.conn, &activity).unwrap()
+                    });
                     self.pages.pop();
                 }
                 MainMessage::ChooseParent { child } => {
                     self.pages
                         .push(Box::new(Picker::new(self.conn.clone(), child)));
+                    return iced::widget::text_input::focus(iced::widget::text_input::Id::new(
+                        "picker",
+                    ));
                 }
                 MainMessage::NoOp => {}
             },
@@ -276,14 +584,30 @@ impl Application for App {
             }
         }
 
+        if is_action {
+            vault::sync(&self.conn);
+        }
         Command::none()
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        let shortcuts = iced::subscription::events_with(Self::shortcut);
+        match self.pages.last() {
+            Some(page) if page.takes_arrow_keys() => {
+                Subscription::batch([shortcuts, iced::subscription::events_with(Self::arrow_keys)])
+            }
+            _ => shortcuts,
+        }
+    }
+
     fn theme(&self) -> Self::Theme {
-        Self::Theme::Dark
+        match config::get().theme {
+            config::Theme::Dark => Self::Theme::Dark,
+            config::Theme::Light => Self::Theme::Light,
+        }
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         if let Some(page) = self.pages.last() {
             page.view()
         } else {
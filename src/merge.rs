@@ -0,0 +1,207 @@
+//! Merging duplicate activities, like "Gym" and "Workout" in different branches, into one.
+//!
+//! The source's sessions, children and note move to the target and the source is
+//! deleted. The sessions move with their copies, so the source's old ancestors lose the
+//! time and the target's ancestors gain it.
+
+use serde::Serialize;
+
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::sql;
+use crate::ActID;
+use crate::Conn;
+
+/// Put between the target's and the source's note when both have one.
+pub const NOTE_SEPARATOR: &str = "\n\n---\n\n";
+
+/// How an activity's total changes with the merge.
+#[derive(Debug, Clone, Serialize)]
+pub struct TotalChange {
+    pub path: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+/// What merging would change, without changing anything.
+#[derive(Debug, Clone, Serialize)]
+pub struct Preview {
+    /// The activities whose total minutes change, except the source which is gone.
+    pub totals: Vec<TotalChange>,
+    /// The paths of the ranked leaves, highest priority first.
+    pub ranking_before: Vec<String>,
+    pub ranking_after: Vec<String>,
+}
+
+pub fn check(conn: &Conn, source: ActID, target: ActID) -> Result<(), String> {
+    if Activity::is_in_subtree(conn, target, source) {
+        Err("Can't merge an activity into itself or one of its descendants".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn merge(conn: &Conn, source: ActID, target: ActID) -> Result<(), String> {
+    check(conn, source, target)?;
+
+    // The source has a copy of every session in its subtree.
+    let statement = format!(
+        "SELECT duration, timestamp FROM history WHERE id = '{}'",
+        source
+    );
+    let sessions = sql::query_map(conn, &statement, |row| {
+        Ok((row.get::<usize, f64>(0)?, row.get::<usize, u64>(1)?))
+    })
+    .unwrap();
+    let ancestors = |id: ActID| {
+        let mut ids = vec![id];
+        while let Some(parent) = Activity::get_parent_index(conn, *ids.last().unwrap()) {
+            ids.push(parent);
+        }
+        ids
+    };
+    let (old, new) = (ancestors(source), ancestors(target));
+    for (duration, timestamp) in sessions {
+        for id in old.iter().filter(|id| !new.contains(id)) {
+            conn.execute(
+                "DELETE FROM history WHERE rowid = (SELECT rowid FROM history
+                    WHERE id = ?1 AND duration = ?2 AND timestamp = ?3 LIMIT 1)",
+                rusqlite::params![id.to_string(), duration, timestamp],
+            )
+            .unwrap();
+        }
+        for id in new.iter().filter(|id| !old.contains(id)) {
+            conn.execute(
+                "INSERT INTO history (id, duration, timestamp) VALUES (?1, ?2, ?3)",
+                rusqlite::params![id.to_string(), duration, timestamp],
+            )
+            .unwrap();
+        }
+    }
+
+    for child in Activity::fetch_all_children(conn, Some(source)) {
+        Activity::set_parent(conn, child.id, Some(target));
+    }
+
+    let note = match (
+        crate::notes::read(conn, target),
+        crate::notes::read(conn, source),
+    ) {
+        (Some(target), Some(source)) => {
+            Some(format!("{}{}{}", target.trim_end(), NOTE_SEPARATOR, source))
+        }
+        (None, source) => source,
+        (target, None) => target,
+    };
+    if let Some(note) = note {
+        crate::notes::write(conn, target, &note);
+    }
+
+    let statement = format!("UPDATE timer SET id = '{}' WHERE id = '{}'", target, source);
+    sql::execute(conn, &statement).unwrap();
+    let statement = format!(
+        "UPDATE slots SET activity = '{}' WHERE activity = '{}'",
+        target, source
+    );
+    sql::execute(conn, &statement).unwrap();
+
+    Activity::delete_activity(conn, source);
+    Activity::normalize_assignments(conn);
+    Ok(())
+}
+
+/// Merges in a transaction that's rolled back, comparing the totals and the ranking.
+pub fn preview(conn: &Conn, source: ActID, target: ActID) -> Result<Preview, String> {
+    check(conn, source, target)?;
+
+    let totals = |conn: &Conn| -> Vec<(ActID, String, f64)> {
+        Activity::fetch_all_activities_flat(conn)
+            .into_iter()
+            .map(|act| {
+                let minutes = Session::get_history(conn, act.id)
+                    .iter()
+                    .map(|session| session.minutes())
+                    .sum::<f64>()
+                    // Removed sessions can leave a rounding error like -0.0.
+                    .max(0.);
+                (act.id, Activity::path(conn, act.id), minutes)
+            })
+            .collect()
+    };
+    let ranking = |conn: &Conn| -> Vec<String> {
+        Activity::fetch_by_priority(conn)
+            .into_iter()
+            .map(|act| Activity::path(conn, act.id))
+            .collect()
+    };
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let (totals_before, ranking_before) = (totals(conn), ranking(conn));
+    merge(conn, source, target)?;
+    let (totals_after, ranking_after) = (totals(conn), ranking(conn));
+    tx.rollback().map_err(|e| e.to_string())?;
+
+    let totals = totals_before
+        .into_iter()
+        .filter_map(|(id, path, before)| {
+            let (_, _, after) = totals_after.iter().find(|(after, _, _)| *after == id)?;
+            ((after - before).abs() >= 0.05).then_some(TotalChange {
+                path,
+                before,
+                after: *after,
+            })
+        })
+        .collect();
+
+    Ok(Preview {
+        totals,
+        ranking_before,
+        ranking_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let conn = sql::init_in_memory();
+        let health = Activity::new("Health".to_string(), None);
+        let gym = Activity::new("Gym".to_string(), Some(health.id));
+        let sports = Activity::new("Sports".to_string(), None);
+        let workout = Activity::new("Workout".to_string(), Some(sports.id));
+        let legs = Activity::new("Legs".to_string(), Some(gym.id));
+        for act in [&health, &gym, &sports, &workout, &legs] {
+            sql::new_activity(&conn, act).unwrap();
+        }
+        Session::log(&conn, legs.id, 30., 1000);
+        Session::log(&conn, workout.id, 10., 2000);
+        crate::notes::write(&conn, gym.id, "Squats");
+        crate::notes::write(&conn, workout.id, "Run\n");
+
+        let preview = preview(&conn, gym.id, workout.id).unwrap();
+        let total = |path: &str| {
+            preview
+                .totals
+                .iter()
+                .find(|t| t.path == path)
+                .unwrap()
+                .after
+        };
+        assert_eq!(total("Sports/Workout").round(), 40.);
+        assert_eq!(total("Health").round(), 0.);
+        assert!(Activity::fetch_activity(&conn, gym.id).is_ok());
+        assert!(merge(&conn, health.id, legs.id).is_err());
+
+        merge(&conn, gym.id, workout.id).unwrap();
+        assert!(Activity::fetch_activity(&conn, gym.id).is_err());
+        assert_eq!(Activity::path(&conn, legs.id), "Sports/Workout/Legs");
+        assert_eq!(Session::get_history(&conn, sports.id).len(), 2);
+        assert!(Session::get_history(&conn, health.id).is_empty());
+        assert_eq!(
+            crate::notes::read(&conn, workout.id).unwrap(),
+            "Run\n\n---\n\nSquats"
+        );
+    }
+}
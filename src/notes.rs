@@ -0,0 +1,181 @@
+//! Notes are stored in the `notes` table. They used to be plain text files in the notes
+//! directory, named after the activity's id, and those are moved into the database by
+//! [`migrate_files`]. The directory is still where notes are put while they're open in an
+//! external editor.
+
+use crate::ActID;
+use crate::Conn;
+
+pub fn dir() -> std::path::PathBuf {
+    let file_path = crate::config::get().notes_dir();
+    std::fs::create_dir_all(&file_path).expect("Failed to create notes directory");
+    file_path
+}
+
+/// Where the note is written while it's open in an external editor.
+pub fn path(id: ActID) -> std::path::PathBuf {
+    let mut file_path = dir();
+    file_path.push(format!("{}.txt", id));
+    file_path
+}
+
+/// The note of an activity, if it has a non-empty one.
+pub fn read(conn: &Conn, id: ActID) -> Option<String> {
+    conn.query_row(
+        "SELECT note FROM notes WHERE id = ?1",
+        [id.to_string()],
+        |row| row.get::<usize, String>(0),
+    )
+    .ok()
+    .filter(|note| !note.trim().is_empty())
+}
+
+/// Sets the note. An empty one is deleted.
+pub fn write(conn: &Conn, id: ActID, note: &str) {
+    if note.trim().is_empty() {
+        return delete(conn, id);
+    }
+    conn.execute(
+        "INSERT INTO notes (id, note) VALUES (?1, ?2)
+            ON CONFLICT (id) DO UPDATE SET note = excluded.note",
+        rusqlite::params![id.to_string(), note],
+    )
+    .unwrap();
+}
+
+pub fn delete(conn: &Conn, id: ActID) {
+    conn.execute("DELETE FROM notes WHERE id = ?1", [id.to_string()])
+        .unwrap();
+}
+
+/// Moves the notes that are still files into the database. The files are moved to the
+/// `migrated` directory next to them rather than deleted. A note already in the database
+/// wins over its file.
+pub fn migrate_files(conn: &Conn) {
+    let dir = dir();
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(id) = uuid::Uuid::parse_str(&file_name) else {
+            continue;
+        };
+        let Ok(note) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+
+        if crate::activity::Activity::fetch_activity(conn, id).is_ok() && read(conn, id).is_none() {
+            write(conn, id, &note);
+        }
+
+        let migrated = dir.join("migrated");
+        std::fs::create_dir_all(&migrated).expect("Failed to create migrated notes directory");
+        let _ = std::fs::rename(entry.path(), migrated.join(file_name));
+    }
+}
+
+/// Shown after an activity's name in the lists when it has a note.
+pub fn marker(conn: &Conn, id: ActID) -> &'static str {
+    if read(conn, id).is_some() {
+        " [note]"
+    } else {
+        ""
+    }
+}
+
+/// Editors that need a terminal to run in.
+const TERMINAL_EDITORS: [&str; 12] = [
+    "vi", "vim", "nvim", "nano", "emacs", "micro", "hx", "helix", "kak", "joe", "ne", "mg",
+];
+
+/// The editor command with its arguments: the `editor` setting, `$VISUAL`, `$EDITOR`, or
+/// else the system's default application.
+fn editor() -> Vec<String> {
+    let var = |name| {
+        std::env::var(name)
+            .ok()
+            .filter(|var| !var.trim().is_empty())
+    };
+    let editor = crate::config::get()
+        .editor
+        .clone()
+        .or_else(|| var("VISUAL"))
+        .or_else(|| var("EDITOR"));
+
+    match editor {
+        Some(editor) => editor.split_whitespace().map(str::to_string).collect(),
+        None if cfg!(target_os = "macos") => vec!["open".to_string(), "-t".to_string()],
+        None if cfg!(target_os = "windows") => vec!["notepad".to_string()],
+        None => vec!["xdg-open".to_string()],
+    }
+}
+
+fn is_terminal_editor(editor: &[String]) -> bool {
+    let program = std::path::Path::new(&editor[0])
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    TERMINAL_EDITORS.contains(&program.as_str())
+}
+
+/// Writes the note to a file for an external editor.
+fn write_file(conn: &Conn, id: ActID) -> Result<std::path::PathBuf, String> {
+    let file_path = path(id);
+    std::fs::write(&file_path, read(conn, id).unwrap_or_default())
+        .map_err(|e| format!("Couldn't write {}: {}", file_path.display(), e))?;
+    Ok(file_path)
+}
+
+/// Stores what was saved in an external editor opened by [`open_in_editor`]. The file is
+/// removed, so it can't overwrite later edits. Saving in the editor again recreates it.
+pub fn pick_up_edits(conn: &Conn, id: ActID) {
+    if let Ok(note) = std::fs::read_to_string(path(id)) {
+        write(conn, id, &note);
+        let _ = std::fs::remove_file(path(id));
+    }
+}
+
+/// Opens the note in an editor without waiting for it, see [`pick_up_edits`]. Terminal
+/// editors are started in the `terminal` setting, `$TERMINAL` or `x-terminal-emulator`.
+pub fn open_in_editor(conn: &Conn, id: ActID) -> Result<(), String> {
+    let file_path = write_file(conn, id)?;
+    let mut command = editor();
+    if is_terminal_editor(&command) {
+        let terminal = crate::config::get()
+            .terminal
+            .clone()
+            .or_else(|| std::env::var("TERMINAL").ok())
+            .unwrap_or_else(|| "x-terminal-emulator".to_string());
+        let mut terminal: Vec<String> = terminal.split_whitespace().map(str::to_string).collect();
+        terminal.push("-e".to_string());
+        terminal.append(&mut command);
+        command = terminal;
+    }
+
+    std::process::Command::new(&command[0])
+        .args(&command[1..])
+        .arg(file_path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Couldn't start {}: {}", command[0], e))
+}
+
+/// Opens the note in an editor in the current terminal, and waits until it's closed.
+pub fn edit_in_terminal(conn: &Conn, id: ActID) -> Result<(), String> {
+    let file_path = write_file(conn, id)?;
+    let command = editor();
+    let status = std::process::Command::new(&command[0])
+        .args(&command[1..])
+        .arg(file_path)
+        .status()
+        .map_err(|e| format!("Couldn't start {}: {}", command[0], e))?;
+
+    if status.success() {
+        pick_up_edits(conn, id);
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", command[0], status))
+    }
+}
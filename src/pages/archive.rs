@@ -0,0 +1,103 @@
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::ActID;
+use crate::Conn;
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, row, Column};
+
+use iced::{Alignment, Command, Element};
+
+/// Lists the archived activities so they can be restored, or purged for good.
+#[derive(Debug)]
+pub struct Archive {
+    /// The activity whose purge button was pressed once.
+    confirm_purge: Option<ActID>,
+    status: String,
+    conn: Conn,
+}
+
+impl Page for Archive {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        match message {
+            PageMessage::RestoreActivity(id) => {
+                self.status = format!("Restored {}", Activity::path(&self.conn, id));
+                crate::journal::record(&self.conn, &self.status, || {
+                    Activity::restore(&self.conn, id)
+                });
+            }
+            PageMessage::PurgeActivity(id) => {
+                let path = Activity::path(&self.conn, id);
+                if self.confirm_purge != Some(id) {
+                    self.status = format!(
+                        "Press purge again to delete {} with its history and notes",
+                        path
+                    );
+                    self.confirm_purge = Some(id);
+                    return Command::none();
+                }
+                self.confirm_purge = None;
+                Activity::purge(&self.conn, id);
+                self.status = format!("Purged {}", path);
+            }
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let archived = Activity::fetch_archived(&self.conn);
+
+        let rows = archived
+            .iter()
+            .map(|act| {
+                let minutes: f64 = Session::get_history(&self.conn, act.id)
+                    .iter()
+                    .map(|session| session.minutes())
+                    .sum();
+                let label = format!(
+                    "{} ({:.0} min)",
+                    Activity::path(&self.conn, act.id),
+                    minutes
+                );
+                row![
+                    iced::widget::text::Text::new(label),
+                    button("Restore").on_press(PageMessage::RestoreActivity(act.id).into_message()),
+                    button("Purge").on_press(PageMessage::PurgeActivity(act.id).into_message()),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .into()
+            })
+            .collect();
+
+        let status = if archived.is_empty() && self.status.is_empty() {
+            "Nothing is archived".to_string()
+        } else {
+            self.status.clone()
+        };
+
+        iced::widget::column![
+            button("Go back").on_press(MainMessage::GoBack.into_message()),
+            Column::with_children(rows).spacing(5),
+            iced::widget::text::Text::new(status),
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+}
+
+impl Archive {
+    pub fn new(conn: Conn) -> Self {
+        Self {
+            confirm_purge: None,
+            status: String::new(),
+            conn,
+        }
+    }
+}
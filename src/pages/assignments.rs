@@ -2,23 +2,28 @@ use crate::activity::Activity;
 use crate::ActID;
 use crate::PageMessage;
 
-use crate::sql;
 use crate::Conn;
 use crate::IntoMessage;
 use crate::MainMessage;
 use crate::Message;
-use iced::widget::text_input;
+use iced::widget::{checkbox, slider, text_input};
 use iced::Renderer;
 
 use iced::{Alignment, Command, Element};
 
 use super::Page;
 
+/// Edits the assigned values of a group of siblings. Moving a slider rebalances the
+/// unlocked others, while typing a value changes only that one.
 #[derive(Debug)]
 pub struct Assignments {
     msg: String,
     activities: Vec<Activity>,
-    conn: Conn,
+    /// Which values the sliders leave alone. Locks only last while the page is open and
+    /// aren't saved, so [`Activity::normalize_assignments`] scales every sibling alike.
+    locked: Vec<bool>,
+    /// The parent's share of all time in percent, which the siblings divide between them.
+    parent_share: f32,
 }
 
 impl Page for Assignments {
@@ -34,17 +39,15 @@ impl Page for Assignments {
                 };
                 self.activities[idx].assigned = num;
             }
+            PageMessage::Slide(idx, value) if !self.locked[idx] => {
+                let nums =
+                    crate::utils::rebalance(self.assigned(), &self.locked, idx, value as i32, 100);
+                self.set_assigned(nums);
+            }
+            PageMessage::Lock(idx, locked) => self.locked[idx] = locked,
             PageMessage::Adjust => {
-                let invec = self
-                    .activities
-                    .clone()
-                    .iter()
-                    .map(|act| act.assigned as i32)
-                    .collect();
-                let normalized_vec = crate::utils::normalize_vec(invec, 100);
-                for (idx, x) in normalized_vec.iter().enumerate() {
-                    self.activities[idx].assigned = *x as u32;
-                }
+                let nums = crate::utils::normalize_unlocked(self.assigned(), &self.locked, 100);
+                self.set_assigned(nums);
             }
             _ => return Command::none(),
         };
@@ -52,7 +55,9 @@ impl Page for Assignments {
     }
 
     fn view(&self) -> Element<'static, Message> {
-        let title = iced::Element::new(iced::widget::text::Text::new("Make diff 0 to submit"));
+        let title = iced::Element::new(iced::widget::text::Text::new(
+            "Make diff 0 to submit. Sliders keep it at 0, locked values stay put.",
+        ));
         let diff = {
             let diff = self.get_diff();
             let diff = format!("Current difference: {}", diff);
@@ -64,11 +69,26 @@ impl Page for Assignments {
                 text_input("", &act.assigned.to_string(), move |s| {
                     PageMessage::InputChanged((idx, s)).into_message()
                 })
-                .padding(20)
-                .width(200)
-                .size(30);
-            let desc = iced::Element::new(iced::widget::text::Text::new(act.text.clone()));
-            let row = iced::Element::new(iced::widget::row![assigned_button, desc]);
+                .padding(10)
+                .width(80)
+                .size(20);
+            let lock = checkbox("Lock", self.locked[idx], move |locked| {
+                PageMessage::Lock(idx, locked).into_message()
+            });
+            let assigned_slider = slider(0..=100, act.assigned, move |value| {
+                PageMessage::Slide(idx, value).into_message()
+            })
+            .width(200);
+            let desc = iced::Element::new(iced::widget::text::Text::new(format!(
+                "{}  ({:.1}% of all time)",
+                act.text,
+                self.parent_share * act.assigned as f32 / 100.
+            )));
+            let row = iced::Element::new(
+                iced::widget::row![lock, assigned_slider, assigned_button, desc]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+            );
             some_vec.push(row);
         }
 
@@ -80,10 +100,12 @@ impl Page for Assignments {
             iced::widget::text::Text::new("Submit"),
         )
         .on_press(if self.get_diff() == 0 {
-            for act in self.activities.iter() {
-                sql::set_assigned(&self.conn, act.id, act.assigned);
-            }
-            MainMessage::GoBack.into_message()
+            let values = self
+                .activities
+                .iter()
+                .map(|act| (act.id, act.assigned))
+                .collect();
+            MainMessage::SetAssigned(values).into_message()
         } else {
             MainMessage::NoOp.into_message()
         });
@@ -115,13 +137,27 @@ impl Assignments {
         tot - 100
     }
 
-    pub fn new(conn: Conn, parent: Option<ActID>) -> Self {
-        let activities = Activity::fetch_children(&conn, parent);
+    fn assigned(&self) -> Vec<i32> {
+        self.activities
+            .iter()
+            .map(|act| act.assigned as i32)
+            .collect()
+    }
+
+    fn set_assigned(&mut self, nums: Vec<i32>) {
+        for (act, num) in self.activities.iter_mut().zip(nums) {
+            act.assigned = num.max(0) as u32;
+        }
+    }
+
+    pub fn new(conn: &Conn, parent: Option<ActID>) -> Self {
+        let activities = Activity::fetch_children(conn, parent);
 
         let mut myself = Self {
             msg: String::new(),
+            locked: vec![false; activities.len()],
+            parent_share: parent.map_or(100., |parent| Activity::get_true_assigned(conn, parent)),
             activities,
-            conn,
         };
 
         let diff = myself.get_diff();
@@ -0,0 +1,127 @@
+use crate::activity::{Activity, DeleteImpact, DeleteMode};
+use crate::ActID;
+use crate::Conn;
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, checkbox, text::Text};
+
+use iced::{Alignment, Command, Element};
+
+/// Asks how to delete an activity, showing what would go with it.
+#[derive(Debug)]
+pub struct DeleteDialog {
+    id: ActID,
+    path: String,
+    parent: Option<String>,
+    impact: DeleteImpact,
+    /// Whether lifting keeps the activity's own sessions as its parent's.
+    merge_sessions: bool,
+}
+
+impl Page for DeleteDialog {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        if let PageMessage::MergeSessions(merge) = message {
+            self.merge_sessions = merge;
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let impact = self.impact;
+        let mut column =
+            iced::widget::column![Text::new(format!("Delete {}?", self.path)).size(30)]
+                .padding(20)
+                .spacing(10)
+                .align_items(Alignment::Center);
+
+        column = column.push(Text::new(format!(
+            "It has {} descendants, {} sessions ({} logged on it directly) and {} notes.",
+            impact.descendants, impact.sessions, impact.direct_sessions, impact.notes
+        )));
+
+        let lift_label = if impact.descendants > 0 {
+            "Delete only this, move its children up"
+        } else {
+            "Delete only this"
+        };
+        let mut lift = iced::widget::column![button(lift_label).on_press(
+            MainMessage::DeleteActivity {
+                id: self.id,
+                mode: DeleteMode::Lift {
+                    merge_sessions: self.merge_sessions && self.parent.is_some(),
+                },
+            }
+            .into_message()
+        )]
+        .spacing(5)
+        .align_items(Alignment::Center);
+        let mut lost = vec![];
+        if impact.has_note {
+            lost.push("its note".to_string());
+        }
+        match &self.parent {
+            Some(parent) if impact.direct_sessions > 0 => {
+                lift = lift.push(checkbox(
+                    format!(
+                        "Keep its {} sessions as sessions of {}",
+                        impact.direct_sessions, parent
+                    ),
+                    self.merge_sessions,
+                    |merge| PageMessage::MergeSessions(merge).into_message(),
+                ));
+                if !self.merge_sessions {
+                    lost.push(format!("{} sessions", impact.direct_sessions));
+                }
+            }
+            None if impact.direct_sessions > 0 => {
+                lost.push(format!("{} sessions", impact.direct_sessions));
+            }
+            _ => {}
+        }
+        if !lost.is_empty() {
+            lift = lift.push(Text::new(format!("Deletes {}", lost.join(" and "))));
+        }
+        column = column.push(lift);
+
+        if impact.descendants > 0 {
+            column = column
+                .push(
+                    button(Text::new(format!(
+                        "Delete the whole subtree ({} activities)",
+                        impact.descendants + 1
+                    )))
+                    .on_press(
+                        MainMessage::DeleteActivity {
+                            id: self.id,
+                            mode: DeleteMode::Subtree,
+                        }
+                        .into_message(),
+                    ),
+                )
+                .push(Text::new(format!(
+                    "Deletes {} sessions and {} notes",
+                    impact.sessions, impact.notes
+                )));
+        }
+
+        column
+            .push(button("Cancel").on_press(MainMessage::GoBack.into_message()))
+            .push(Text::new("Deleting can be undone with Ctrl+Z"))
+            .into()
+    }
+}
+
+impl DeleteDialog {
+    pub fn new(conn: &Conn, id: ActID) -> Self {
+        Self {
+            id,
+            path: Activity::path(conn, id),
+            parent: Activity::get_parent(conn, id).map(|parent| parent.text),
+            impact: Activity::delete_impact(conn, id),
+            merge_sessions: true,
+        }
+    }
+}
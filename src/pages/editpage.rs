@@ -6,15 +6,30 @@ use crate::MainMessage;
 use crate::Message;
 use crate::Page;
 use crate::PageMessage;
-use iced::widget::{button, text_input};
+use iced::widget::{button, row, text_input};
 
 use iced::{Alignment, Command, Element, Renderer};
 
+/// Edits to the note, which is edited line by line with a text input for each.
+#[derive(Debug, Clone)]
+pub enum NoteMessage {
+    Line(usize, String),
+    /// Enter was pressed on a line, so a new one goes below it.
+    NewLine(usize),
+    RemoveLine(usize),
+    Save,
+    Reload,
+    OpenEditor,
+}
+
 #[derive(Debug)]
 pub struct EditPage {
     pub activity: Activity,
-    pub assigned: String,
     pub session_duration: String,
+    /// The name being typed, which is saved on enter or when going back.
+    name: String,
+    note_lines: Vec<String>,
+    status: String,
     conn: Conn,
 }
 
@@ -24,15 +39,15 @@ impl Page for EditPage {
             text_input("New session", &self.session_duration, |s| {
                 PageMessage::InputChanged((0, s)).into_message()
             })
-            .on_submit(self.maybe_add_session())
+            .on_submit(self.log_session())
             .padding(20)
             .size(30);
 
         let text_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
-            text_input("Edit name", &self.activity.text, |s| {
+            text_input("Edit name", &self.name, |s| {
                 PageMessage::InputChanged((1, s)).into_message()
             })
-            .on_submit(MainMessage::GoBack.into_message())
+            .on_submit(self.save_name())
             .padding(20)
             .size(30);
 
@@ -43,20 +58,36 @@ impl Page for EditPage {
             .into_message(),
         );
 
-        let view_note = button("View note").on_press(
-            MainMessage::EditNote {
-                id: self.activity.id,
+        let export_button = button("Export").on_press(
+            MainMessage::NewExport {
+                subtree: Some(self.activity.id),
+            }
+            .into_message(),
+        );
+
+        let import_button = button("Import time logs here").on_press(
+            MainMessage::NewImport {
+                parent: Some(self.activity.id),
             }
             .into_message(),
         );
 
         iced::widget::column![
+            iced::widget::text::Text::new(self.activity.display_timestamps()),
             session_input,
             text_input,
-            button("go back to main").on_press(MainMessage::GoBack.into_message()),
-            button("Delete").on_press(MainMessage::DeleteActivity(self.activity.id).into_message()),
+            button("go back to main").on_press(self.save_name()),
+            button("Archive")
+                .on_press(MainMessage::ArchiveActivity(self.activity.id).into_message()),
+            button("Delete").on_press(MainMessage::NewDelete(self.activity.id).into_message()),
+            button("Merge into another")
+                .on_press(MainMessage::NewMerge(self.activity.id).into_message()),
+            button("Split into children")
+                .on_press(MainMessage::NewSplit(self.activity.id).into_message()),
             child_button,
-            view_note,
+            export_button,
+            import_button,
+            self.view_note(),
         ]
         .padding(20)
         .align_items(Alignment::Center)
@@ -65,14 +96,11 @@ impl Page for EditPage {
 
     fn update(&mut self, message: PageMessage) -> iced::Command<Message> {
         match message {
-            PageMessage::InputChanged((0, s)) => {
-                if s.is_empty() || s.parse::<u32>().is_ok() {
-                    self.session_duration = s;
-                }
-            }
-            PageMessage::InputChanged((1, s)) => {
-                self.activity.modify_text(s, &self.conn);
+            PageMessage::InputChanged((0, s)) if s.is_empty() || s.parse::<u32>().is_ok() => {
+                self.session_duration = s;
             }
+            PageMessage::InputChanged((1, s)) => self.name = s,
+            PageMessage::Note(message) => return self.update_note(message),
             _ => {}
         };
         Command::none()
@@ -81,49 +109,122 @@ impl Page for EditPage {
 
 impl EditPage {
     pub fn new(conn: Conn, id: ActID) -> Self {
+        let activity = Activity::fetch_activity(&conn, id).unwrap();
         Self {
-            activity: Activity::fetch_activity(&conn, id).unwrap(),
-            assigned: String::default(),
+            name: activity.text.clone(),
+            activity,
             session_duration: String::default(),
+            note_lines: Self::read_note(&conn, id),
+            status: String::new(),
             conn,
         }
     }
 
-    fn maybe_add_session(&self) -> Message {
-        if self.session_duration.parse::<f64>().is_ok() {
-            self.new_session();
+    fn read_note(conn: &Conn, id: ActID) -> Vec<String> {
+        match crate::notes::read(conn, id) {
+            Some(note) => note.lines().map(str::to_string).collect(),
+            None => vec![String::new()],
         }
-        MainMessage::GoBack.into_message()
     }
 
-    pub fn new_session(&self) {
-        let timestamp = crate::utils::current_unix().as_secs();
-        let duration = self.session_duration.parse::<f64>().unwrap();
-        let mut id = self.activity.id;
-
-        let statement =
-            "INSERT INTO history (id, duration, timestamp) VALUES (?1, ?2, ?3)".to_string();
-        self.conn
-            .execute(
-                &statement,
-                rusqlite::params![id.to_string(), duration, timestamp],
-            )
-            .unwrap();
-    
-
-    while let Some(parent) = Activity::get_parent(&self.conn, id){
-        id = parent.id;
-        self.conn
-            .execute(
-                &statement,
-                rusqlite::params![id.to_string(), duration, timestamp],
-            )
-            .unwrap();
+    fn note_line_id(idx: usize) -> text_input::Id {
+        text_input::Id::new(format!("note-line-{}", idx))
+    }
+
+    fn view_note(&self) -> Element<'static, Message> {
+        let mut lines = iced::widget::column![iced::widget::text::Text::new("Note")].spacing(5);
+        for (idx, line) in self.note_lines.iter().enumerate() {
+            let input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+                text_input("", line, move |s| {
+                    PageMessage::Note(NoteMessage::Line(idx, s)).into_message()
+                })
+                .on_submit(PageMessage::Note(NoteMessage::NewLine(idx)).into_message())
+                .id(Self::note_line_id(idx))
+                .padding(5);
+            let remove = button("x")
+                .on_press(PageMessage::Note(NoteMessage::RemoveLine(idx)).into_message());
+            lines = lines.push(row![input, remove].spacing(5));
+        }
 
-    
+        let note_button = |label: &str, message: NoteMessage| {
+            button(iced::widget::text::Text::new(label.to_string()))
+                .on_press(PageMessage::Note(message).into_message())
+        };
+
+        lines
+            .push(
+                row![
+                    note_button("Save note", NoteMessage::Save),
+                    note_button("Open in editor", NoteMessage::OpenEditor),
+                    note_button("Reload", NoteMessage::Reload),
+                ]
+                .spacing(10),
+            )
+            .push(iced::widget::text::Text::new(self.status.clone()))
+            .into()
     }
+
+    fn update_note(&mut self, message: NoteMessage) -> Command<Message> {
+        let id = self.activity.id;
+        match message {
+            NoteMessage::Line(idx, s) => self.note_lines[idx] = s,
+            NoteMessage::NewLine(idx) => {
+                self.note_lines.insert(idx + 1, String::new());
+                return text_input::focus(Self::note_line_id(idx + 1));
+            }
+            NoteMessage::RemoveLine(idx) => {
+                self.note_lines.remove(idx);
+                if self.note_lines.is_empty() {
+                    self.note_lines.push(String::new());
+                }
+            }
+            NoteMessage::Save => {
+                let note = self.note_lines.join("\n");
+                crate::notes::write(&self.conn, id, &format!("{}\n", note));
+                self.status = if note.trim().is_empty() {
+                    "Note removed".to_string()
+                } else {
+                    "Note saved".to_string()
+                };
+            }
+            NoteMessage::Reload => {
+                crate::notes::pick_up_edits(&self.conn, id);
+                self.note_lines = Self::read_note(&self.conn, id);
+                self.status.clear();
+            }
+            NoteMessage::OpenEditor => {
+                self.status = match crate::notes::open_in_editor(&self.conn, id) {
+                    Ok(()) => "Press reload after saving in the editor".to_string(),
+                    Err(e) => e,
+                };
+            }
+        }
+        Command::none()
     }
 
-        
+    /// Logs the typed session and goes back, if it's a number of minutes.
+    fn log_session(&self) -> Message {
+        match self.session_duration.parse::<f64>() {
+            Ok(minutes) if minutes > 0. => MainMessage::LogSession {
+                id: self.activity.id,
+                minutes,
+            }
+            .into_message(),
+            _ => MainMessage::NoOp.into_message(),
+        }
+    }
 
+    /// Goes back, renaming the activity first if the name was changed.
+    fn save_name(&self) -> Message {
+        let name = self.name.trim();
+        if name.is_empty() || name == self.activity.text {
+            MainMessage::GoBack.into_message()
+        } else {
+            MainMessage::RenameActivity {
+                id: self.activity.id,
+                text: name.to_string(),
+            }
+            .into_message()
+        }
+    }
 }
@@ -0,0 +1,145 @@
+use crate::export::{Filter, Format};
+use crate::ActID;
+use crate::Conn;
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, row, text_input};
+
+use iced::{Alignment, Command, Element, Renderer};
+
+#[derive(Debug)]
+pub struct Export {
+    subtree: Option<ActID>,
+    from: String,
+    to: String,
+    dir: String,
+    status: String,
+    conn: Conn,
+}
+
+impl Page for Export {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        match message {
+            PageMessage::InputChanged((0, s)) => self.from = s,
+            PageMessage::InputChanged((1, s)) => self.to = s,
+            PageMessage::InputChanged((2, s)) => self.dir = s,
+            PageMessage::Export(format) => self.export(format),
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let scope = match self.subtree {
+            Some(id) => format!(
+                "Exporting: {}",
+                crate::activity::Activity::path(&self.conn, id)
+            ),
+            None => "Exporting: everything".to_string(),
+        };
+        let scope = iced::Element::new(iced::widget::text::Text::new(scope));
+
+        let from_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("From (YYYY-MM-DD)", &self.from, |s| {
+                PageMessage::InputChanged((0, s)).into_message()
+            })
+            .padding(10)
+            .size(20);
+
+        let to_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("To (YYYY-MM-DD)", &self.to, |s| {
+                PageMessage::InputChanged((1, s)).into_message()
+            })
+            .padding(10)
+            .size(20);
+
+        let dir_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("Directory", &self.dir, |s| {
+                PageMessage::InputChanged((2, s)).into_message()
+            })
+            .padding(10)
+            .size(20);
+
+        let ics_button =
+            button("Export .ics").on_press(PageMessage::Export(Format::Ics).into_message());
+        let csv_button =
+            button("Export .csv").on_press(PageMessage::Export(Format::Csv).into_message());
+        let md_button =
+            button("Export .md").on_press(PageMessage::Export(Format::Markdown).into_message());
+        let opml_button =
+            button("Export .opml").on_press(PageMessage::Export(Format::Opml).into_message());
+        let json_button = button("Export everything as .json")
+            .on_press(PageMessage::Export(Format::Json).into_message());
+
+        let status = iced::Element::new(iced::widget::text::Text::new(self.status.clone()));
+
+        iced::widget::column![
+            button("Go back").on_press(MainMessage::GoBack.into_message()),
+            scope,
+            from_input,
+            to_input,
+            dir_input,
+            row![ics_button, csv_button, json_button].spacing(10),
+            row![md_button, opml_button].spacing(10),
+            status,
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+}
+
+impl Export {
+    pub fn new(conn: Conn, subtree: Option<ActID>) -> Self {
+        let mut dir = crate::sql::data_dir();
+        dir.push("exports");
+
+        Self {
+            subtree,
+            from: String::new(),
+            to: String::new(),
+            dir: dir.to_string_lossy().into_owned(),
+            status: String::new(),
+            conn,
+        }
+    }
+
+    /// Builds the filter from the input fields. The "to" date is inclusive.
+    fn filter(&self) -> Result<Filter, String> {
+        let parse = |s: &str| -> Result<Option<u64>, String> {
+            if s.trim().is_empty() {
+                Ok(None)
+            } else {
+                crate::utils::parse_date(s)
+                    .map(Some)
+                    .ok_or(format!("Invalid date: {}", s))
+            }
+        };
+
+        Ok(Filter {
+            from: parse(&self.from)?,
+            to: parse(&self.to)?.map(|to| to + 86400),
+            subtree: self.subtree,
+        })
+    }
+
+    fn export(&mut self, format: Format) {
+        let filter = match self.filter() {
+            Ok(filter) => filter,
+            Err(e) => {
+                self.status = e;
+                return;
+            }
+        };
+
+        let dir = std::path::PathBuf::from(&self.dir);
+        self.status = match crate::export::export_to_dir(&self.conn, format, &filter, &dir) {
+            Ok(path) => format!("Wrote {}", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        };
+    }
+}
@@ -0,0 +1,133 @@
+use crate::import::{Format, Mode};
+use crate::ActID;
+use crate::Conn;
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, row, text_input, Row};
+
+use iced::{Alignment, Command, Element, Renderer};
+
+#[derive(Debug)]
+pub struct Import {
+    format: Format,
+    /// Where activities from outlines and other time trackers are created.
+    parent: Option<ActID>,
+    path: String,
+    status: String,
+    conn: Conn,
+}
+
+impl Page for Import {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        match message {
+            PageMessage::InputChanged((0, s)) => self.path = s,
+            PageMessage::ImportFormat(format) => self.format = format,
+            PageMessage::Import { mode, dry_run } => self.import(mode, dry_run),
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let formats = Format::ALL
+            .iter()
+            .map(|format| {
+                let label = if *format == self.format {
+                    format!("[{}]", format.name())
+                } else {
+                    format.name().to_string()
+                };
+                button(iced::widget::text::Text::new(label))
+                    .on_press(PageMessage::ImportFormat(*format).into_message())
+                    .into()
+            })
+            .collect();
+
+        let path_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("Path to file", &self.path, |s| {
+                PageMessage::InputChanged((0, s)).into_message()
+            })
+            .padding(10)
+            .size(20);
+
+        let import_button = |label: &'static str, mode: Mode, dry_run: bool| {
+            button(label).on_press(PageMessage::Import { mode, dry_run }.into_message())
+        };
+
+        let buttons: Element<'static, Message> = if self.format == Format::Acts {
+            iced::widget::column![
+                row![
+                    import_button("Preview merge", Mode::Merge, true),
+                    import_button("Merge", Mode::Merge, false),
+                ]
+                .spacing(10),
+                row![
+                    import_button("Preview replace", Mode::Replace, true),
+                    import_button("Replace everything", Mode::Replace, false),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10)
+            .into()
+        } else {
+            let target = match self.parent {
+                Some(id) => format!(
+                    "Activities are created under: {}",
+                    crate::activity::Activity::path(&self.conn, id)
+                ),
+                None => "Activities are created at the root".to_string(),
+            };
+            iced::widget::column![
+                iced::widget::text::Text::new(target),
+                row![
+                    import_button("Preview", Mode::Merge, true),
+                    import_button("Import", Mode::Merge, false),
+                ]
+                .spacing(10),
+            ]
+            .spacing(10)
+            .into()
+        };
+
+        let status = iced::Element::new(iced::widget::text::Text::new(self.status.clone()));
+
+        iced::widget::column![
+            button("Go back").on_press(MainMessage::GoBack.into_message()),
+            Row::with_children(formats).spacing(10),
+            path_input,
+            buttons,
+            status,
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+}
+
+impl Import {
+    pub fn new(conn: Conn, parent: Option<ActID>) -> Self {
+        Self {
+            format: Format::Acts,
+            parent,
+            path: String::new(),
+            status: String::new(),
+            conn,
+        }
+    }
+
+    fn import(&mut self, mode: Mode, dry_run: bool) {
+        let path = std::path::PathBuf::from(&self.path);
+        let result =
+            crate::import::import_file(&self.conn, self.format, &path, mode, self.parent, dry_run);
+
+        self.status = match result {
+            Ok(summary) if dry_run => format!("Would change {}", summary),
+            Ok(summary) => format!("Imported {}", summary),
+            Err(e) => format!("Import failed: {}", e),
+        };
+    }
+}
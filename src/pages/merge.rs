@@ -0,0 +1,158 @@
+use crate::activity::Activity;
+use crate::merge::Preview;
+use crate::ActID;
+use crate::Conn;
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, row, text::Text, text_input, Column};
+
+use iced::{Alignment, Command, Element, Renderer};
+
+/// How many places of the ranking the preview shows.
+const RANKING_LEN: usize = 10;
+
+/// Picks the activity to merge `source` into, and shows what the merge would change.
+#[derive(Debug)]
+pub struct Merge {
+    source: ActID,
+    filter: String,
+    target: Option<ActID>,
+    preview: Option<Result<Preview, String>>,
+    conn: Conn,
+}
+
+impl Page for Merge {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        match message {
+            PageMessage::InputChanged((0, s)) => self.filter = s,
+            PageMessage::PickAct(target) => {
+                self.target = target;
+                self.preview =
+                    target.map(|target| crate::merge::preview(&self.conn, self.source, target));
+            }
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let filter_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("Filter", &self.filter, |s| {
+                PageMessage::InputChanged((0, s)).into_message()
+            })
+            .padding(10)
+            .size(20);
+
+        let mut column = iced::widget::column![
+            button("Go back").on_press(MainMessage::GoBack.into_message()),
+            Text::new(format!(
+                "Merge {} into",
+                Activity::path(&self.conn, self.source)
+            ))
+            .size(30),
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        match (&self.target, &self.preview) {
+            (Some(target), Some(Ok(preview))) => {
+                column = column
+                    .push(Text::new(Activity::path(&self.conn, *target)).size(30))
+                    .push(Self::view_preview(preview))
+                    .push(
+                        row![
+                            button("Merge").on_press(
+                                MainMessage::MergeActivity {
+                                    source: self.source,
+                                    target: *target,
+                                }
+                                .into_message()
+                            ),
+                            button("Pick another")
+                                .on_press(PageMessage::PickAct(None).into_message()),
+                        ]
+                        .spacing(10),
+                    );
+            }
+            (_, Some(Err(e))) => {
+                column = column.push(Text::new(e.clone())).push(
+                    button("Pick another").on_press(PageMessage::PickAct(None).into_message()),
+                );
+            }
+            _ => {
+                column = column
+                    .push(filter_input)
+                    .push(Column::with_children(self.view_candidates()).spacing(5));
+            }
+        }
+
+        column.into()
+    }
+}
+
+impl Merge {
+    pub fn new(conn: Conn, source: ActID) -> Self {
+        Self {
+            source,
+            filter: String::new(),
+            target: None,
+            preview: None,
+            conn,
+        }
+    }
+
+    /// The activities the source can be merged into that match the filter, by path.
+    fn view_candidates(&self) -> Vec<Element<'static, Message>> {
+        let filter = self.filter.to_lowercase();
+        let mut candidates: Vec<(String, ActID)> = Activity::fetch_all_activities_flat(&self.conn)
+            .into_iter()
+            .filter(|act| !Activity::is_in_subtree(&self.conn, act.id, self.source))
+            .map(|act| (Activity::path(&self.conn, act.id), act.id))
+            .filter(|(path, _)| path.to_lowercase().contains(&filter))
+            .collect();
+        candidates.sort();
+
+        candidates
+            .into_iter()
+            .map(|(path, id)| {
+                button(Text::new(path))
+                    .on_press(PageMessage::PickAct(Some(id)).into_message())
+                    .into()
+            })
+            .collect()
+    }
+
+    fn view_preview(preview: &Preview) -> Element<'static, Message> {
+        let mut totals = iced::widget::column![Text::new("Total time")].spacing(5);
+        for change in &preview.totals {
+            totals = totals.push(Text::new(format!(
+                "{}: {:.0}m -> {:.0}m",
+                change.path, change.before, change.after
+            )));
+        }
+
+        let ranking = |title: &str, paths: &[String]| {
+            let mut column = iced::widget::column![Text::new(title.to_string())].spacing(5);
+            for (idx, path) in paths.iter().take(RANKING_LEN).enumerate() {
+                column = column.push(Text::new(format!("{}. {}", idx + 1, path)));
+            }
+            column
+        };
+
+        iced::widget::column![
+            totals,
+            row![
+                ranking("Ranking now", &preview.ranking_before),
+                ranking("After merging", &preview.ranking_after),
+            ]
+            .spacing(40),
+        ]
+        .spacing(20)
+        .align_items(Alignment::Center)
+        .into()
+    }
+}
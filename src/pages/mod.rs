@@ -1,8 +1,17 @@
 //pub mod assignments;
+pub mod archive;
 pub mod assignments;
+pub mod delete;
 pub mod editpage;
+pub mod export;
+pub mod import;
+pub mod merge;
 pub mod new_activity;
 pub mod picker;
+pub mod profiles;
+pub mod search;
+pub mod settings;
+pub mod split;
 pub mod treeview;
 
 use crate::IntoMessage;
@@ -15,6 +24,11 @@ pub trait Page: Debug {
     fn update(&mut self, _message: PageMessage) -> Command<Message> {
         panic!("");
     }
+
+    /// Whether the page gets the up and down arrow keys as [`PageMessage::MoveSelection`].
+    fn takes_arrow_keys(&self) -> bool {
+        false
+    }
 }
 
 use std::fmt::Debug;
@@ -22,6 +36,7 @@ use std::fmt::Debug;
 use crate::{ActID, Conn, Message, PageMessage};
 use iced::widget::text_input;
 
+#[allow(dead_code)]
 pub struct ValueGetter {
     pub title: String,
     pub input: String,
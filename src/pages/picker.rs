@@ -1,6 +1,7 @@
 use crate::ActID;
 
 use crate::activity::Activity;
+use crate::picker::Candidate;
 
 use crate::Conn;
 use crate::MainMessage;
@@ -10,120 +11,155 @@ use crate::PageMessage;
 
 use crate::IntoMessage;
 
-use iced::widget::Column;
+use iced::widget::{button, text::Text, text_input, Column};
 
-use iced::{Alignment, Element};
+use iced::{Alignment, Command, Element, Renderer};
 
+/// Picks a new parent for `child`. Typing filters the activities by name, the arrow keys
+/// move the selection and enter moves the child there. The first entry stands for the root.
 #[derive(Debug)]
 pub struct Picker {
     child: ActID,
+    filter: String,
+    /// 0 is the root, the rest index into the candidates shifted by one.
+    selected: usize,
     conn: Conn,
 }
 
 impl Page for Picker {
-    fn update(&mut self, _message: PageMessage) -> iced::Command<Message> {
-        todo!()
+    fn update(&mut self, message: PageMessage) -> iced::Command<Message> {
+        match message {
+            PageMessage::InputChanged((0, s)) => {
+                self.filter = s;
+                // The best guess is the first match rather than the root.
+                self.selected = if self.filter.trim().is_empty() || self.candidates().is_empty() {
+                    0
+                } else {
+                    1
+                };
+            }
+            PageMessage::MoveSelection(delta) => {
+                let len = self.candidates().len() + 1;
+                self.selected = self.selected.saturating_add_signed(delta).min(len - 1);
+            }
+            _ => {}
+        }
+        Command::none()
     }
+
     fn view(&self) -> Element<'static, Message> {
-        let some_vec = self.view_recursive();
+        let candidates = self.candidates();
+        let current = Activity::get_parent_index(&self.conn, self.child);
 
         let back_button: iced::widget::button::Button<Message> =
             iced::widget::button(iced::widget::text::Text::new("Go back"))
                 .on_press(MainMessage::GoBack.into_message());
 
+        let filter_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("Type to filter, arrows to select", &self.filter, |s| {
+                PageMessage::InputChanged((0, s)).into_message()
+            })
+            .on_submit(self.choose(self.parent_at(&candidates, self.selected)))
+            .id(text_input::Id::new("picker"))
+            .padding(10)
+            .size(20);
+
+        let label = |idx: usize, text: String, parent: Option<ActID>| {
+            let marker = if idx == self.selected { "> " } else { "" };
+            let here = if parent == current { " (current)" } else { "" };
+            format!("{}{}{}", marker, text, here)
+        };
+
         let root_button: iced::widget::button::Button<Message> =
-            iced::widget::button(iced::widget::text::Text::new("Root")).on_press(
-                MainMessage::SetParent {
-                    child: self.child,
-                    parent: None,
-                }
-                .into_message(),
-            );
-
-        iced::widget::column![root_button, Column::with_children(some_vec), back_button,]
-            .padding(20)
-            .align_items(Alignment::Center)
-            .into()
+            iced::widget::button(Text::new(label(0, "Move to root".to_string(), None)))
+                .on_press(self.choose(None));
+
+        let rows: Vec<Element<'static, Message>> = candidates
+            .iter()
+            .enumerate()
+            .map(|(idx, candidate)| {
+                let padding = " ".repeat(candidate.depth * 6);
+                let text = label(idx + 1, candidate.label.clone(), Some(candidate.id));
+                iced::widget::row![
+                    Text::new(padding),
+                    button(Text::new(text)).on_press(self.choose(Some(candidate.id))),
+                ]
+                .into()
+            })
+            .collect();
+
+        let status = if candidates.is_empty() && !self.filter.trim().is_empty() {
+            "No activity matches the filter".to_string()
+        } else {
+            self.preview(self.parent_at(&candidates, self.selected), current)
+        };
+
+        iced::widget::column![
+            back_button,
+            Text::new(format!(
+                "Move {} under",
+                Activity::path(&self.conn, self.child)
+            ))
+            .size(30),
+            filter_input,
+            root_button,
+            Column::with_children(rows),
+            Text::new(status),
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    fn takes_arrow_keys(&self) -> bool {
+        true
     }
 }
 
 impl Picker {
     pub fn new(conn: Conn, child: ActID) -> Self {
-        Self { conn, child }
+        Self {
+            child,
+            filter: String::new(),
+            selected: 0,
+            conn,
+        }
     }
 
-    fn _view_recursive(&self) -> Vec<Element<'static, Message>> {
-        fn recursive(
-            conn: &Conn,
-            elms: &mut Vec<Element<'static, Message>>,
-            parent: Option<ActID>,
-            depth: usize,
-            chosen_kid: ActID,
-        ) {
-            let kids = Activity::fetch_children(conn, parent);
-
-            for kid in kids {
-                let padding = " ".repeat(depth * 6);
-                let padding = iced::Element::new(iced::widget::text::Text::new(padding));
-
-                let single_pad = iced::Element::new(iced::widget::text::Text::new(" "));
-                let single_pad2 = iced::Element::new(iced::widget::text::Text::new(" "));
-
-                let edit_button: iced::widget::button::Button<Message> =
-                    iced::widget::button(iced::widget::text::Text::new(kid.text)).on_press({
-                        Activity::set_parent(conn, chosen_kid, Some(kid.id));
-                        MainMessage::GoBack.into_message()
-                    });
-
-                let row = iced::Element::new(iced::widget::row![
-                    single_pad,
-                    padding,
-                    edit_button,
-                    single_pad2,
-                ]);
-                elms.push(row);
-                recursive(conn, elms, Some(kid.id), depth + 1, chosen_kid);
-            }
-        }
+    fn candidates(&self) -> Vec<Candidate> {
+        crate::picker::candidates(&self.conn, self.child, &self.filter)
+    }
 
-        let mut elms = vec![];
-        recursive(&self.conn, &mut elms, None, 1, self.child);
-        elms
+    fn parent_at(&self, candidates: &[Candidate], idx: usize) -> Option<ActID> {
+        match idx {
+            0 => None,
+            idx => candidates.get(idx - 1).map(|candidate| candidate.id),
+        }
     }
 
-    fn view_recursive(&self) -> Vec<Element<'static, Message>> {
-        fn recursive(
-            conn: &Conn,
-            elms: &mut Vec<Element<'static, Message>>,
-            parent: Option<ActID>,
-            depth: usize,
-            child: ActID,
-        ) {
-            let kids = Activity::fetch_children(conn, parent);
-
-            for kid in kids {
-                let padding = " ".repeat(depth * 6);
-                let padding = iced::Element::new(iced::widget::text::Text::new(padding));
-
-                let single_pad = iced::Element::new(iced::widget::text::Text::new("   "));
-
-                let edit_button: iced::widget::button::Button<Message> =
-                    iced::widget::button(iced::widget::text::Text::new(kid.text)).on_press(
-                        MainMessage::SetParent {
-                            child,
-                            parent: Some(kid.id),
-                        }
-                        .into_message(),
-                    );
-
-                let row = iced::Element::new(iced::widget::row![single_pad, padding, edit_button,]);
-                elms.push(row);
-                recursive(conn, elms, Some(kid.id), depth + 1, child);
-            }
+    fn choose(&self, parent: Option<ActID>) -> Message {
+        MainMessage::SetParent {
+            child: self.child,
+            parent,
         }
+        .into_message()
+    }
 
-        let mut elms = vec![];
-        recursive(&self.conn, &mut elms, None, 0, self.child);
-        elms
+    /// What the child's share of all time would be under the selected parent.
+    fn preview(&self, parent: Option<ActID>, current: Option<ActID>) -> String {
+        let now = Activity::get_true_assigned(&self.conn, self.child);
+        if parent == current {
+            return format!("Already there, with {:.1}% of all time", now);
+        }
+        let share = crate::picker::effective_share(&self.conn, self.child, parent);
+        let place = match parent {
+            Some(parent) => Activity::path(&self.conn, parent),
+            None => "the root".to_string(),
+        };
+        format!(
+            "Under {} it would get {:.1}% of all time, now {:.1}%",
+            place, share, now
+        )
     }
 }
@@ -0,0 +1,125 @@
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, row, text_input};
+
+use iced::{Alignment, Command, Element, Renderer};
+
+#[derive(Debug, Default)]
+pub struct Profiles {
+    new_name: String,
+    /// The name profiles are renamed to.
+    rename_to: String,
+    /// The profile whose delete button was pressed once.
+    confirm_delete: Option<String>,
+    status: String,
+}
+
+impl Page for Profiles {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        match message {
+            PageMessage::InputChanged((0, s)) => self.new_name = s,
+            PageMessage::InputChanged((1, s)) => self.rename_to = s,
+            PageMessage::ValueSubmit => {
+                let name = self.new_name.trim().to_string();
+                self.status = match crate::profile::create(&name) {
+                    Ok(()) => {
+                        self.new_name.clear();
+                        format!("Created {}", name)
+                    }
+                    Err(e) => e,
+                };
+            }
+            PageMessage::RenameProfile(name) => {
+                let new = self.rename_to.trim().to_string();
+                self.status = match crate::profile::rename(&name, &new) {
+                    Ok(()) => {
+                        self.rename_to.clear();
+                        format!("Renamed {} to {}", name, new)
+                    }
+                    Err(e) => e,
+                };
+            }
+            PageMessage::DeleteProfile(name) => {
+                if self.confirm_delete.as_ref() != Some(&name) {
+                    self.status = format!(
+                        "Press delete again to delete {} with all its activities",
+                        name
+                    );
+                    self.confirm_delete = Some(name);
+                    return Command::none();
+                }
+                self.confirm_delete = None;
+                self.status = match crate::profile::delete(&name) {
+                    Ok(()) => format!("Deleted {}", name),
+                    Err(e) => e,
+                };
+            }
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let mut column =
+            iced::widget::column![button("Go back").on_press(MainMessage::GoBack.into_message())]
+                .padding(20)
+                .spacing(10)
+                .align_items(Alignment::Center);
+
+        for profile in crate::profile::list() {
+            let label = if profile.active {
+                format!("{} (active)", profile.name)
+            } else {
+                profile.name.clone()
+            };
+            let mut switch_button = button(iced::widget::text::Text::new(label));
+            if !profile.active {
+                switch_button = switch_button
+                    .on_press(MainMessage::SwitchProfile(profile.name.clone()).into_message());
+            }
+
+            let mut profile_row = row![switch_button].spacing(10);
+            if profile.name != crate::profile::DEFAULT {
+                profile_row =
+                    profile_row
+                        .push(button("Rename").on_press(
+                            PageMessage::RenameProfile(profile.name.clone()).into_message(),
+                        ))
+                        .push(button("Delete").on_press(
+                            PageMessage::DeleteProfile(profile.name.clone()).into_message(),
+                        ));
+            }
+            column = column.push(profile_row);
+        }
+
+        let rename_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("New name for the profile to rename", &self.rename_to, |s| {
+                PageMessage::InputChanged((1, s)).into_message()
+            })
+            .padding(10)
+            .size(20);
+
+        let new_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("New profile", &self.new_name, |s| {
+                PageMessage::InputChanged((0, s)).into_message()
+            })
+            .on_submit(PageMessage::ValueSubmit.into_message())
+            .padding(10)
+            .size(20);
+
+        column
+            .push(rename_input)
+            .push(
+                row![
+                    new_input,
+                    button("Create").on_press(PageMessage::ValueSubmit.into_message())
+                ]
+                .spacing(10),
+            )
+            .push(iced::widget::text::Text::new(self.status.clone()))
+            .into()
+    }
+}
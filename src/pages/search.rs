@@ -0,0 +1,80 @@
+use crate::search::Hit;
+use crate::Conn;
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, text_input, Column};
+
+use iced::{Alignment, Command, Element, Renderer};
+
+/// Searches activity names and notes. Picking a hit opens it on the edit page.
+#[derive(Debug)]
+pub struct Search {
+    query: String,
+    hits: Vec<Hit>,
+    conn: Conn,
+}
+
+impl Page for Search {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        if let PageMessage::InputChanged((0, s)) = message {
+            self.hits = crate::search::search(&self.conn, &s);
+            self.query = s;
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let search_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("Search names and notes", &self.query, |s| {
+                PageMessage::InputChanged((0, s)).into_message()
+            })
+            .id(text_input::Id::new("search"))
+            .padding(10)
+            .size(20);
+
+        let hits = self
+            .hits
+            .iter()
+            .map(|hit| {
+                let label = if hit.snippet.is_empty() {
+                    hit.path.clone()
+                } else {
+                    format!("{}\n{}", hit.path, hit.snippet)
+                };
+                button(iced::widget::text::Text::new(label))
+                    .on_press(MainMessage::NewEdit(hit.id).into_message())
+                    .into()
+            })
+            .collect();
+
+        let status = if !self.query.trim().is_empty() && self.hits.is_empty() {
+            "No matches"
+        } else {
+            ""
+        };
+
+        iced::widget::column![
+            button("Go back").on_press(MainMessage::GoBack.into_message()),
+            search_input,
+            iced::widget::text::Text::new(status),
+            Column::with_children(hits).spacing(5),
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+}
+
+impl Search {
+    pub fn new(conn: Conn) -> Self {
+        Self {
+            query: String::new(),
+            hits: vec![],
+            conn,
+        }
+    }
+}
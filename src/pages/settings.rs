@@ -0,0 +1,157 @@
+use crate::config::{Layer, Theme};
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, row, text_input};
+
+use iced::{Alignment, Command, Element, Renderer};
+
+/// Edits the config file. Empty fields are left out of the file, so they use the default.
+#[derive(Debug)]
+pub struct Settings {
+    /// db_path, notes_dir, editor, terminal, budget_hours, decay, vault_dir, in the order
+    /// of their inputs.
+    fields: [String; 7],
+    theme: Option<Theme>,
+    /// Kept as it is, profiles are picked on their own page.
+    profile: Option<String>,
+    status: String,
+}
+
+const FIELDS: [(&str, &str); 7] = [
+    ("db_path", "Database"),
+    ("notes_dir", "Notes directory"),
+    ("editor", "Editor"),
+    ("terminal", "Terminal for terminal editors"),
+    ("budget_hours", "Daily budget in hours"),
+    ("decay", "Decay per day"),
+    ("vault_dir", "Markdown vault"),
+];
+
+impl Page for Settings {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        match message {
+            PageMessage::InputChanged((idx, s)) => self.fields[idx] = s,
+            PageMessage::SetTheme(theme) => self.theme = Some(theme),
+            PageMessage::ValueSubmit => self.save(),
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let config = crate::config::get();
+        let defaults = [
+            config.db_path().to_string_lossy().into_owned(),
+            config.notes_dir().to_string_lossy().into_owned(),
+            config
+                .editor
+                .clone()
+                .unwrap_or("$VISUAL or $EDITOR".to_string()),
+            config.terminal.clone().unwrap_or("$TERMINAL".to_string()),
+            config.budget_hours.to_string(),
+            config.decay.to_string(),
+            config
+                .vault_dir
+                .as_ref()
+                .map(|dir| dir.to_string_lossy().into_owned())
+                .unwrap_or("Not mirrored".to_string()),
+        ];
+
+        let mut column = iced::widget::column![
+            button("Go back").on_press(MainMessage::GoBack.into_message()),
+            iced::Element::new(iced::widget::text::Text::new(format!(
+                "Config file: {}",
+                crate::config::path().display()
+            ))),
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        for (idx, (_, label)) in FIELDS.iter().enumerate() {
+            let input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+                text_input(&defaults[idx], &self.fields[idx], move |s| {
+                    PageMessage::InputChanged((idx, s)).into_message()
+                })
+                .padding(10)
+                .size(20);
+            column = column.push(row![iced::widget::text::Text::new(*label), input].spacing(10));
+        }
+
+        let theme = self.theme.unwrap_or(config.theme);
+        let theme_button = |name: &str, value: Theme| {
+            let label = if theme == value {
+                format!("[{}]", name)
+            } else {
+                name.to_string()
+            };
+            button(iced::widget::text::Text::new(label))
+                .on_press(PageMessage::SetTheme(value).into_message())
+        };
+
+        column
+            .push(
+                row![
+                    iced::widget::text::Text::new("Theme"),
+                    theme_button("Dark", Theme::Dark),
+                    theme_button("Light", Theme::Light)
+                ]
+                .spacing(10),
+            )
+            .push(button("Save").on_press(PageMessage::ValueSubmit.into_message()))
+            .push(iced::widget::text::Text::new(self.status.clone()))
+            .into()
+    }
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        let (file, status) = match crate::config::read_file() {
+            Ok((file, warnings)) => (file, warnings.join("\n")),
+            Err(e) => (Layer::default(), e),
+        };
+
+        let field = |value: Option<String>| value.unwrap_or_default();
+        Self {
+            fields: [
+                field(file.db_path),
+                field(file.notes_dir),
+                field(file.editor),
+                field(file.terminal),
+                field(file.budget_hours.map(|hours| hours.to_string())),
+                field(file.decay.map(|decay| decay.to_string())),
+                field(file.vault_dir),
+            ],
+            theme: file.theme,
+            profile: file.profile,
+            status,
+        }
+    }
+
+    fn save(&mut self) {
+        let mut file = Layer {
+            theme: self.theme,
+            profile: self.profile.clone(),
+            ..Default::default()
+        };
+        for (idx, (key, _)) in FIELDS.iter().enumerate() {
+            if self.fields[idx].trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = file.set(key, &self.fields[idx]) {
+                self.status = e;
+                return;
+            }
+        }
+
+        self.status = match crate::config::save(&file) {
+            Ok(()) => {
+                "Saved. A new database or notes directory is used after a restart.".to_string()
+            }
+            Err(e) => format!("Saving failed: {}", e),
+        };
+    }
+}
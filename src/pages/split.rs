@@ -0,0 +1,183 @@
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::split::Distribution;
+use crate::utils::UtcDateTime;
+use crate::ActID;
+use crate::Conn;
+use crate::IntoMessage;
+use crate::MainMessage;
+use crate::Message;
+use crate::Page;
+use crate::PageMessage;
+use iced::widget::{button, checkbox, radio, row, text::Text, text_input};
+
+use iced::{Alignment, Command, Element, Renderer};
+
+/// The text inputs of a part, in the order of their `InputChanged` indices.
+const PART_FIELDS: usize = 3;
+
+/// A part as it's being typed, with its share of each session for the percentage split.
+#[derive(Debug, Default)]
+struct PartInput {
+    name: String,
+    assigned: String,
+    percent: String,
+}
+
+/// Splits an activity into new children and hands them its sessions.
+#[derive(Debug)]
+pub struct Split {
+    id: ActID,
+    path: String,
+    parts: Vec<PartInput>,
+    by_session: bool,
+    /// The sessions logged on the activity itself, with the part each one goes to.
+    sessions: Vec<(Session, Option<usize>)>,
+}
+
+impl Page for Split {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        match message {
+            PageMessage::InputChanged((idx, s)) => {
+                let part = &mut self.parts[idx / PART_FIELDS];
+                match idx % PART_FIELDS {
+                    0 => part.name = s,
+                    1 if s.is_empty() || s.parse::<u32>().is_ok() => part.assigned = s,
+                    2 if s.is_empty() || s.parse::<u32>().is_ok() => part.percent = s,
+                    _ => {}
+                }
+            }
+            PageMessage::AddPart => self.parts.push(PartInput::default()),
+            PageMessage::SplitBySession(by_session) => self.by_session = by_session,
+            PageMessage::SessionPart(session, part) => self.sessions[session].1 = part,
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'static, Message> {
+        let mut column = iced::widget::column![
+            button("Go back").on_press(MainMessage::GoBack.into_message()),
+            Text::new(format!("Split {} into", self.path)).size(30),
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        for (idx, part) in self.parts.iter().enumerate() {
+            let input = |placeholder: &str, value: &str, field: usize| {
+                let input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+                    text_input(placeholder, value, move |s| {
+                        PageMessage::InputChanged((idx * PART_FIELDS + field, s)).into_message()
+                    })
+                    .padding(10);
+                input
+            };
+            let mut part_row = row![
+                input("Name", &part.name, 0),
+                input("Assigned %", &part.assigned, 1).width(120),
+            ]
+            .spacing(10);
+            if !self.by_session {
+                part_row = part_row.push(input("% of sessions", &part.percent, 2).width(120));
+            }
+            column = column.push(part_row);
+        }
+        column = column.push(button("Add part").on_press(PageMessage::AddPart.into_message()));
+
+        if !self.sessions.is_empty() {
+            column = column.push(checkbox(
+                format!(
+                    "Hand over the {} sessions one by one instead of by percentage",
+                    self.sessions.len()
+                ),
+                self.by_session,
+                |by_session| PageMessage::SplitBySession(by_session).into_message(),
+            ));
+        }
+        if self.by_session {
+            for (idx, (session, owner)) in self.sessions.iter().enumerate() {
+                let date = UtcDateTime::from_unix(session.timestamp);
+                let mut session_row = row![Text::new(format!(
+                    "{} {}  {:.0}m",
+                    date.date(),
+                    date.time(),
+                    session.minutes()
+                ))]
+                .spacing(10);
+                session_row = session_row.push(radio("Keep", None, Some(*owner), move |part| {
+                    PageMessage::SessionPart(idx, part).into_message()
+                }));
+                for (part_idx, part) in self.parts.iter().enumerate() {
+                    session_row = session_row.push(radio(
+                        part.name.clone(),
+                        Some(part_idx),
+                        Some(*owner),
+                        move |part| PageMessage::SessionPart(idx, part).into_message(),
+                    ));
+                }
+                column = column.push(session_row);
+            }
+        }
+
+        let (submit, problem) = match self.submit() {
+            Ok(message) => (message.into_message(), String::new()),
+            Err(e) => (MainMessage::NoOp.into_message(), e),
+        };
+        column
+            .push(button("Split").on_press(submit))
+            .push(Text::new(problem))
+            .into()
+    }
+}
+
+impl Split {
+    pub fn new(conn: &Conn, id: ActID) -> Self {
+        Self {
+            id,
+            path: Activity::path(conn, id),
+            parts: vec![PartInput::default(), PartInput::default()],
+            by_session: false,
+            sessions: Session::fetch_direct_sessions_of(conn, id)
+                .into_iter()
+                .map(|session| (session, None))
+                .collect(),
+        }
+    }
+
+    /// The split as it's been filled in, or what's missing.
+    fn submit(&self) -> Result<MainMessage, String> {
+        let number = |s: &str| s.parse::<u32>().ok();
+        let parts = crate::split::fill_assigned(
+            self.parts
+                .iter()
+                .map(|part| (part.name.trim().to_string(), number(&part.assigned)))
+                .collect(),
+        );
+        if parts.iter().any(|part| part.name.is_empty()) {
+            return Err("Every part needs a name".to_string());
+        }
+
+        let distribution = if self.by_session {
+            Distribution::Sessions(self.sessions.iter().map(|(_, owner)| *owner).collect())
+        } else if self.parts.iter().all(|part| part.percent.is_empty()) {
+            Distribution::by_assigned(&parts)
+        } else {
+            let percents: Vec<u32> = self
+                .parts
+                .iter()
+                .map(|part| number(&part.percent).unwrap_or(0))
+                .collect();
+            if percents.iter().sum::<u32>() != 100 {
+                return Err("The shares of the sessions have to add up to 100%".to_string());
+            }
+            Distribution::Percent(percents)
+        };
+
+        Ok(MainMessage::SplitActivity {
+            id: self.id,
+            parts,
+            distribution,
+        })
+    }
+}
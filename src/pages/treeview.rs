@@ -1,25 +1,55 @@
+use std::collections::HashSet;
+
 use crate::ActID;
 
-use crate::activity::Activity;
+use crate::activity::{Activity, Reorder};
+use crate::history::Session;
 
 use crate::Conn;
 use crate::MainMessage;
 use crate::Message;
 use crate::Page;
-
+use crate::PageMessage;
 
 use crate::IntoMessage;
 
-use iced::widget::Column;
+use iced::widget::{button, row, text::Text, text_input, Column};
 
-use iced::{Alignment, Element};
+use iced::{Alignment, Command, Element, Renderer};
 
+/// The tree of activities. Collapsed activities hide their descendants, which is
+/// remembered across sessions, and a filter shows only the matching activities and
+/// their ancestors.
 #[derive(Debug)]
 pub struct TreeView {
     conn: Conn,
+    collapsed: HashSet<ActID>,
+    filter: String,
 }
 
 impl Page for TreeView {
+    fn update(&mut self, message: PageMessage) -> Command<Message> {
+        match message {
+            PageMessage::InputChanged((0, s)) => self.filter = s,
+            PageMessage::ToggleCollapsed(id) => {
+                let collapsed = !self.collapsed.contains(&id);
+                crate::sql::set_collapsed(&self.conn, id, collapsed);
+            }
+            PageMessage::SetAllCollapsed(collapsed) => {
+                let parents: HashSet<ActID> = Activity::fetch_all_activities_flat(&self.conn)
+                    .into_iter()
+                    .filter_map(|act| act.parent)
+                    .collect();
+                for id in parents {
+                    crate::sql::set_collapsed(&self.conn, id, collapsed);
+                }
+            }
+            _ => {}
+        }
+        self.collapsed = crate::sql::fetch_collapsed(&self.conn);
+        Command::none()
+    }
+
     fn view(&self) -> Element<'static, Message> {
         let some_vec = self.view_recursive();
 
@@ -27,62 +57,127 @@ impl Page for TreeView {
             iced::widget::button(iced::widget::text::Text::new("Go back"))
                 .on_press(MainMessage::GoBack.into_message());
 
-        iced::widget::column![back_button, Column::with_children(some_vec)]
-            .padding(20)
-            .align_items(Alignment::Center)
-            .into()
+        let filter_input: iced::widget::text_input::TextInput<'_, Message, Renderer> =
+            text_input("Filter", &self.filter, |s| {
+                PageMessage::InputChanged((0, s)).into_message()
+            })
+            .padding(10)
+            .size(20);
+
+        let controls = row![
+            button("Expand all").on_press(PageMessage::SetAllCollapsed(false).into_message()),
+            button("Collapse all").on_press(PageMessage::SetAllCollapsed(true).into_message()),
+        ]
+        .spacing(10);
+
+        iced::widget::column![
+            back_button,
+            filter_input,
+            controls,
+            Column::with_children(some_vec)
+        ]
+        .padding(20)
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
     }
 }
 
 impl TreeView {
     pub fn new(conn: Conn) -> Self {
-        Self { conn }
+        Self {
+            collapsed: crate::sql::fetch_collapsed(&conn),
+            filter: String::new(),
+            conn,
+        }
     }
 
     fn view_recursive(&self) -> Vec<Element<'static, Message>> {
         fn recursive(
-            conn: &Conn,
+            view: &TreeView,
+            shown: Option<&HashSet<ActID>>,
             elms: &mut Vec<Element<'static, Message>>,
             parent: Option<ActID>,
             depth: usize,
         ) {
+            let conn = &view.conn;
             let kids = Activity::fetch_children(conn, parent);
 
             for kid in kids {
+                if shown.is_some_and(|shown| !shown.contains(&kid.id)) {
+                    continue;
+                }
+
+                let has_children = !Activity::fetch_children(conn, Some(kid.id)).is_empty();
+                // The filter shows the matches wherever they are.
+                let expanded = shown.is_some() || !view.collapsed.contains(&kid.id);
+
+                let toggle: Element<'static, Message> = if has_children {
+                    button(Text::new(if expanded { "-" } else { "+" }))
+                        .on_press(PageMessage::ToggleCollapsed(kid.id).into_message())
+                        .into()
+                } else {
+                    Text::new("   ").into()
+                };
+
                 let padding = " ".repeat(depth * 6);
                 let padding = iced::Element::new(iced::widget::text::Text::new(padding));
 
                 let single_pad = iced::Element::new(iced::widget::text::Text::new(" "));
 
-                //let elm = iced::Element::new(iced::widget::text::Text::new(kid.display(conn)));
-
                 let assigned: iced::widget::button::Button<Message> = iced::widget::button(
                     iced::widget::text::Text::new(format!("{}%", kid.assigned)),
                 )
                 .on_press(MainMessage::NewAssign(kid.id).into_message());
 
+                let label = format!("{}{}", kid.text, crate::notes::marker(conn, kid.id));
                 let edit_button: iced::widget::button::Button<Message> =
-                    iced::widget::button(iced::widget::text::Text::new(kid.text))
+                    iced::widget::button(iced::widget::text::Text::new(label))
                         .on_press(MainMessage::NewEdit(kid.id).into_message());
 
+                let total = crate::utils::format_minutes(Session::total_minutes(conn, kid.id));
+
                 let parent_button: iced::widget::button::Button<Message> =
                     iced::widget::button(iced::widget::text::Text::new(":"))
                         .on_press(MainMessage::ChooseParent { child: kid.id }.into_message());
 
+                let reorder = |label: &str, to: Reorder| {
+                    iced::widget::button(iced::widget::text::Text::new(label.to_string()))
+                        .on_press(MainMessage::Reorder { id: kid.id, to }.into_message())
+                };
+
                 let row = iced::Element::new(iced::widget::row![
                     parent_button,
                     padding,
+                    toggle,
                     edit_button,
                     single_pad,
+                    iced::widget::text::Text::new(format!("{} ", total)),
                     assigned,
+                    iced::widget::text::Text::new(" "),
+                    reorder("^^", Reorder::Top),
+                    reorder("^", Reorder::Up),
+                    reorder("v", Reorder::Down),
+                    reorder("vv", Reorder::Bottom),
                 ]);
                 elms.push(row);
-                recursive(conn, elms, Some(kid.id), depth + 1);
+                if expanded {
+                    recursive(view, shown, elms, Some(kid.id), depth + 1);
+                }
             }
         }
 
+        let shown = if self.filter.trim().is_empty() {
+            None
+        } else {
+            Some(Activity::matching(&self.conn, self.filter.trim()))
+        };
+
         let mut elms = vec![];
-        recursive(&self.conn, &mut elms, None, 0);
+        recursive(self, shown.as_ref(), &mut elms, None, 0);
+        if shown.is_some() && elms.is_empty() {
+            elms.push(Text::new("No activity matches the filter").into());
+        }
         elms
     }
 }
@@ -0,0 +1,130 @@
+//! Choosing a new parent for an activity.
+
+use crate::activity::Activity;
+use crate::ActID;
+use crate::Conn;
+
+/// An activity the moved one can be put under.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub id: ActID,
+    /// The name, or with a filter the whole path so it's clear where a match is.
+    pub label: String,
+    /// How deep it is in the tree, which is 0 for everything when filtering.
+    pub depth: usize,
+}
+
+/// The activities `child` can be moved under, in the order of the tree. Its own subtree is
+/// left out, as moving it there would make a cycle. With a filter, only the activities
+/// whose name contains it, ignoring case, are candidates.
+pub fn candidates(conn: &Conn, child: ActID, filter: &str) -> Vec<Candidate> {
+    fn recursive(
+        conn: &Conn,
+        child: ActID,
+        filter: &str,
+        candidates: &mut Vec<Candidate>,
+        parent: Option<ActID>,
+        depth: usize,
+    ) {
+        for kid in Activity::fetch_children(conn, parent) {
+            if kid.id == child {
+                continue;
+            }
+            if filter.is_empty() {
+                candidates.push(Candidate {
+                    id: kid.id,
+                    label: kid.text.clone(),
+                    depth,
+                });
+            } else if kid.text.to_lowercase().contains(filter) {
+                candidates.push(Candidate {
+                    id: kid.id,
+                    label: Activity::path(conn, kid.id),
+                    depth: 0,
+                });
+            }
+            recursive(conn, child, filter, candidates, Some(kid.id), depth + 1);
+        }
+    }
+
+    let mut candidates = vec![];
+    let filter = filter.trim().to_lowercase();
+    recursive(conn, child, &filter, &mut candidates, None, 0);
+    candidates
+}
+
+/// The share of all time, in percent, that `child` would get under `parent` once the
+/// assigned values of its new siblings are normalized. This is worked out from the
+/// assigned values alone, without moving anything.
+pub fn effective_share(conn: &Conn, child: ActID, parent: Option<ActID>) -> f32 {
+    let moved = Activity::fetch_activity(conn, child).unwrap();
+
+    // What `member` would get among its siblings, with the child taken out of its own
+    // and put last under the new parent, like `set_parent` does.
+    let share_in = |group: Option<ActID>, member: ActID| -> f32 {
+        let mut kids: Vec<Activity> = Activity::fetch_children(conn, group)
+            .into_iter()
+            .filter(|kid| kid.id != child)
+            .collect();
+        if group == parent {
+            kids.push(moved.clone());
+        }
+        let nums = kids.iter().map(|kid| kid.assigned as i32).collect();
+        let normalized = crate::utils::normalize_vec(nums, 100);
+        let idx = kids.iter().position(|kid| kid.id == member).unwrap();
+        normalized[idx] as f32
+    };
+
+    let mut share = share_in(parent, child);
+    let mut next = parent;
+    while let Some(id) = next {
+        next = Activity::get_parent_index(conn, id);
+        share *= share_in(next, id) / 100.;
+    }
+    share
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql;
+
+    #[test]
+    fn test_picker() {
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        let piano = Activity::new("Piano".to_string(), Some(music.id));
+        let scales = Activity::new("Scales".to_string(), Some(piano.id));
+        let sports = Activity::new("Sports".to_string(), None);
+        for act in [&music, &piano, &scales, &sports] {
+            sql::new_activity(&conn, act).unwrap();
+        }
+        Activity::normalize_assignments(&conn);
+
+        let ids = |filter| -> Vec<ActID> {
+            candidates(&conn, piano.id, filter)
+                .into_iter()
+                .map(|candidate| candidate.id)
+                .collect()
+        };
+        assert_eq!(ids(""), vec![music.id, sports.id]);
+        assert_eq!(ids("SPO"), vec![sports.id]);
+        assert_eq!(candidates(&conn, scales.id, "pia")[0].label, "Music/Piano");
+
+        // Moving Piano under its sibling Guitar leaves Guitar with all of Music's half.
+        let guitar = Activity::new("Guitar".to_string(), Some(music.id));
+        sql::new_activity(&conn, &guitar).unwrap();
+        Activity::normalize_assignments(&conn);
+        assert_eq!(effective_share(&conn, piano.id, Some(guitar.id)), 50.);
+        Activity::delete_activity(&conn, guitar.id);
+        Activity::normalize_assignments(&conn);
+
+        // Under Sports, Piano would be the only child and get all of Sports' half.
+        assert_eq!(effective_share(&conn, piano.id, Some(sports.id)), 50.);
+        assert_eq!(Activity::get_parent_index(&conn, piano.id), Some(music.id));
+        // At the root, its 100 is normalized against Music's and Sports' 50.
+        assert_eq!(effective_share(&conn, piano.id, None), 50.);
+        assert_eq!(effective_share(&conn, scales.id, Some(sports.id)), 50.);
+        assert_eq!(Activity::get_true_assigned(&conn, scales.id), 50.);
+    }
+}
@@ -0,0 +1,131 @@
+//! Profiles keep separate sets of activities apart, like work and personal ones. Each one
+//! has its own database and notes directory in `profiles/<name>` of the data directory.
+//!
+//! The default profile is the data that was there before profiles existed. It uses the
+//! `db_path` and `notes_dir` settings, and can't be renamed or deleted.
+//!
+//! The profile is picked with the `profile` setting, e.g. `acts --profile work`.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+pub const DEFAULT: &str = "default";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    pub name: String,
+    pub active: bool,
+    pub db_path: PathBuf,
+}
+
+/// The directory of a profile that isn't the default one.
+pub fn dir(name: &str) -> PathBuf {
+    crate::sql::data_dir().join("profiles").join(name)
+}
+
+pub fn active() -> String {
+    crate::config::get().profile.clone()
+}
+
+/// Profile names end up in paths, so they're kept to letters, digits, `-` and `_`.
+pub fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid profile name '{}', use letters, digits, '-' and '_'",
+            name
+        ))
+    }
+}
+
+fn exists(name: &str) -> bool {
+    name == DEFAULT || dir(name).is_dir()
+}
+
+/// All profiles, the default one first.
+pub fn list() -> Vec<Profile> {
+    let mut names: Vec<String> = std::fs::read_dir(crate::sql::data_dir().join("profiles"))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| check_name(name).is_ok() && name != DEFAULT)
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.insert(0, DEFAULT.to_string());
+
+    let active = active();
+    names
+        .into_iter()
+        .map(|name| Profile {
+            active: name == active,
+            db_path: match name.as_str() {
+                DEFAULT if active == DEFAULT => crate::config::get().db_path(),
+                DEFAULT => crate::config::Config::default().db_path(),
+                _ => dir(&name).join("mydb.db"),
+            },
+            name,
+        })
+        .collect()
+}
+
+pub fn create(name: &str) -> Result<(), String> {
+    check_name(name)?;
+    if exists(name) {
+        return Err(format!("Profile '{}' already exists", name));
+    }
+    std::fs::create_dir_all(dir(name).join("notes")).map_err(|e| e.to_string())
+}
+
+/// Renames a profile. If it's the active one, the app switches to the new name.
+pub fn rename(old: &str, new: &str) -> Result<(), String> {
+    check_name(new)?;
+    if old == DEFAULT {
+        return Err("The default profile can't be renamed".to_string());
+    }
+    if !exists(old) {
+        return Err(format!("No profile named '{}'", old));
+    }
+    if exists(new) {
+        return Err(format!("Profile '{}' already exists", new));
+    }
+
+    std::fs::rename(dir(old), dir(new)).map_err(|e| e.to_string())?;
+    if active() == old {
+        crate::config::switch_profile(new);
+    }
+    Ok(())
+}
+
+/// Deletes a profile with its database and notes.
+pub fn delete(name: &str) -> Result<(), String> {
+    if name == DEFAULT {
+        return Err("The default profile can't be deleted".to_string());
+    }
+    if !exists(name) {
+        return Err(format!("No profile named '{}'", name));
+    }
+    if active() == name {
+        return Err("The active profile can't be deleted, switch to another one first".to_string());
+    }
+    std::fs::remove_dir_all(dir(name)).map_err(|e| e.to_string())
+}
+
+/// Switches to a profile until the app is closed, creating it if it doesn't exist.
+pub fn switch(name: &str) -> Result<(), String> {
+    check_name(name)?;
+    if !exists(name) {
+        create(name)?;
+    }
+    crate::config::switch_profile(name);
+    Ok(())
+}
@@ -0,0 +1,228 @@
+//! What the read-only commands report, shared by their text and JSON output.
+//!
+//! The JSON output (`--json`) is an object or an array of objects of these shapes. Fields
+//! are named like the corresponding fields of [`Activity`] and [`Session`]. Durations are
+//! in minutes, timestamps in unix seconds.
+//!
+//! - `list`: an array of [`Ranked`], highest priority first unless `--sort` is given.
+//! - `tree`: an array of [`TreeNode`], the roots.
+//! - `stats`: a [`Stats`].
+//! - `sessions`: an array of [`SessionReport`], oldest first.
+//! - `plan list`: an array of [`SlotReport`], earliest first.
+//! - `timer`: a [`TimerReport`], or `null` if no timer is running.
+//! - `search`: an array of [`crate::search::Hit`], best match first.
+
+use serde::Serialize;
+
+use crate::activity::{Activity, SortKey};
+use crate::export::Filter;
+use crate::history::Session;
+use crate::slots::Slot;
+use crate::timer::Timer;
+use crate::ActID;
+use crate::Conn;
+
+/// A leaf in the priority list. `score` and `daily_weighted_minutes` are the two numbers
+/// the GUI's main list shows.
+#[derive(Debug, Clone, Serialize)]
+pub struct Ranked {
+    pub id: ActID,
+    pub text: String,
+    pub path: String,
+    pub parent: Option<ActID>,
+    pub assigned: u32,
+    pub priority: f32,
+    pub score: f32,
+    pub daily_weighted_minutes: f64,
+}
+
+/// An activity with its descendants. `effective` is the share of all time it's assigned,
+/// i.e. the product of its own and its ancestors' assigned values, as a percentage.
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeNode {
+    pub id: ActID,
+    pub text: String,
+    pub parent: Option<ActID>,
+    pub assigned: u32,
+    pub effective: f32,
+    pub children: Vec<TreeNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    pub id: ActID,
+    pub text: String,
+    pub path: String,
+    pub parent: Option<ActID>,
+    pub assigned: u32,
+    pub effective: f32,
+    pub priority: f32,
+    pub score: f32,
+    /// Sessions on the activity and its descendants.
+    pub sessions: usize,
+    pub total_minutes: f64,
+    pub weighted_minutes: f64,
+    pub daily_weighted_minutes: f64,
+    /// When the last session on the activity or its descendants was logged.
+    pub last_session: Option<u64>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionReport {
+    /// The id of the activity the session was logged on.
+    pub id: ActID,
+    pub path: String,
+    pub duration: f64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotReport {
+    pub slot: uuid::Uuid,
+    /// The id of the activity the slot is planned for.
+    pub id: ActID,
+    pub path: String,
+    pub start: u64,
+    pub duration: f64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerReport {
+    /// The id of the activity being timed.
+    pub id: ActID,
+    pub path: String,
+    pub started: u64,
+    pub elapsed: f64,
+}
+
+impl Ranked {
+    pub fn new(conn: &Conn, activity: &Activity) -> Self {
+        Self {
+            id: activity.id,
+            text: activity.text.clone(),
+            path: Activity::path(conn, activity.id),
+            parent: activity.parent,
+            assigned: activity.assigned,
+            priority: activity.priority,
+            score: activity.priority.powf(0.5),
+            daily_weighted_minutes: daily_weighted_minutes(conn, activity.id),
+        }
+    }
+
+    pub fn all(conn: &Conn) -> Vec<Self> {
+        Self::sorted(conn, SortKey::Priority)
+    }
+
+    pub fn sorted(conn: &Conn, key: SortKey) -> Vec<Self> {
+        Activity::fetch_sorted(conn, key)
+            .iter()
+            .map(|act| Self::new(conn, act))
+            .collect()
+    }
+}
+
+impl TreeNode {
+    pub fn new(conn: &Conn, activity: Activity) -> Self {
+        Self {
+            effective: Activity::get_true_assigned(conn, activity.id),
+            children: Activity::fetch_children(conn, Some(activity.id))
+                .into_iter()
+                .map(|kid| Self::new(conn, kid))
+                .collect(),
+            id: activity.id,
+            text: activity.text,
+            parent: activity.parent,
+            assigned: activity.assigned,
+        }
+    }
+
+    pub fn roots(conn: &Conn) -> Vec<Self> {
+        Activity::fetch_children(conn, None)
+            .into_iter()
+            .map(|act| Self::new(conn, act))
+            .collect()
+    }
+}
+
+impl Stats {
+    pub fn new(conn: &Conn, id: ActID) -> Self {
+        let activity = Activity::fetch_activity(conn, id).unwrap();
+        let history = Session::get_history(conn, id);
+        let now = crate::utils::current_unix().as_secs();
+        let priority = Activity::calculate_priority(conn, id);
+
+        Self {
+            id,
+            path: Activity::path(conn, id),
+            parent: activity.parent,
+            assigned: activity.assigned,
+            effective: Activity::get_true_assigned(conn, id),
+            priority,
+            score: priority.powf(0.5),
+            sessions: history.len(),
+            total_minutes: history.iter().map(|s| s.minutes()).sum(),
+            weighted_minutes: history.iter().map(|s| s.weighted_minutes(now)).sum(),
+            daily_weighted_minutes: daily_weighted_minutes(conn, id),
+            last_session: activity.last_session_at,
+            created_at: activity.created_at,
+            updated_at: activity.updated_at,
+            text: activity.text,
+        }
+    }
+}
+
+impl SessionReport {
+    pub fn new(conn: &Conn, session: &Session) -> Self {
+        Self {
+            id: session.id,
+            path: Activity::path(conn, session.id),
+            duration: session.minutes(),
+            timestamp: session.timestamp,
+        }
+    }
+
+    pub fn all(conn: &Conn, filter: &Filter) -> Vec<Self> {
+        filter
+            .sessions(conn)
+            .into_iter()
+            .map(|session| Self::new(conn, &session))
+            .collect()
+    }
+}
+
+impl SlotReport {
+    pub fn all(conn: &Conn, filter: &Filter) -> Vec<Self> {
+        filter
+            .slots(conn)
+            .into_iter()
+            .map(|slot: Slot| Self {
+                slot: slot.id,
+                id: slot.activity,
+                path: Activity::path(conn, slot.activity),
+                start: slot.start,
+                duration: slot.length.as_secs_f64() / 60.,
+                title: slot.title,
+            })
+            .collect()
+    }
+}
+
+impl TimerReport {
+    pub fn running(conn: &Conn) -> Option<Self> {
+        let timer = Timer::running(conn)?;
+        let now = crate::utils::current_unix().as_secs();
+        Some(Self {
+            id: timer.id,
+            path: Activity::path(conn, timer.id),
+            started: timer.started,
+            elapsed: timer.elapsed(now).as_secs_f64() / 60.,
+        })
+    }
+}
+
+fn daily_weighted_minutes(conn: &Conn, id: ActID) -> f64 {
+    Session::average_daily_weighted_time_spent_from_activity(conn, id).as_secs_f64() / 60.
+}
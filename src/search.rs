@@ -0,0 +1,97 @@
+//! Full-text search over activity names and notes, using SQLite's FTS5 index.
+
+use serde::Serialize;
+
+use crate::activity::Activity;
+use crate::ActID;
+use crate::Conn;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Hit {
+    pub id: ActID,
+    pub path: String,
+    /// The part of the note around the match, with the matched words in `[brackets]`.
+    /// Empty if only the name matched.
+    pub snippet: String,
+}
+
+/// Turns what's typed into an FTS5 query where every word has to match the start of a
+/// word, so the FTS5 syntax never gets in the way.
+fn to_query(input: &str) -> Option<String> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+/// The activities whose name or note contain every word of the input, best matches first.
+pub fn search(conn: &Conn, input: &str) -> Vec<Hit> {
+    let Some(query) = to_query(input) else {
+        return vec![];
+    };
+
+    let mut statement = conn
+        .prepare(
+            "SELECT id, snippet(search, 2, '[', ']', '...', 8) FROM search
+                WHERE search MATCH ?1 ORDER BY rank LIMIT 100",
+        )
+        .unwrap();
+    let rows = statement
+        .query_map([query], |row| {
+            Ok((
+                row.get::<usize, String>(0)?,
+                row.get::<usize, Option<String>>(1)?,
+            ))
+        })
+        .unwrap();
+
+    rows.filter_map(|row| row.ok())
+        .filter_map(|(id, snippet)| {
+            let id = uuid::Uuid::parse_str(&id).ok()?;
+            Some(Hit {
+                id,
+                path: Activity::path(conn, id),
+                snippet: snippet
+                    .filter(|snippet| snippet.contains('['))
+                    .map(|snippet| snippet.trim().to_string())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_names_and_notes() {
+        let conn = crate::sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        let mut piano = Activity::new("Piano".to_string(), Some(music.id));
+        crate::sql::new_activity(&conn, &music).unwrap();
+        crate::sql::new_activity(&conn, &piano).unwrap();
+        crate::notes::write(&conn, piano.id, "Practice the Chopin nocturne");
+
+        let ids = |input: &str| -> Vec<ActID> {
+            search(&conn, input).into_iter().map(|hit| hit.id).collect()
+        };
+        assert_eq!(ids("mus"), vec![music.id]);
+        assert_eq!(ids("chop"), vec![piano.id]);
+        assert_eq!(
+            search(&conn, "chopin")[0].snippet,
+            "Practice the [Chopin] nocturne"
+        );
+        assert!(ids("\"unbalanced").is_empty());
+
+        piano.modify_text("Keys".to_string(), &conn).unwrap();
+        assert!(ids("piano").is_empty());
+        Activity::delete_activity(&conn, piano.id);
+        assert!(ids("chopin").is_empty());
+    }
+}
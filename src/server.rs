@@ -0,0 +1,304 @@
+//! A small REST API on top of the same logic as the front-ends, started with `acts serve`.
+//!
+//! Request and response bodies are JSON. Activities are addressed by id. Errors are
+//! answered with a 4xx or 5xx status and `{"error": "..."}`. The response shapes are the
+//! ones described in [`crate::report`].
+//!
+//! | Method   | Path                        | Body                                  | Response          |
+//! |----------|-----------------------------|---------------------------------------|-------------------|
+//! | `GET`    | `/activities`               |                                       | `[TreeNode]`      |
+//! | `GET`    | `/activities/ranked`        |                                       | `[Ranked]`        |
+//! | `POST`   | `/activities`               | `{"text", "parent"?}`                 | `Stats`           |
+//! | `GET`    | `/activities/{id}`          |                                       | `Stats`           |
+//! | `PATCH`  | `/activities/{id}`          | `{"text"?, "parent"?}` (`null`: root) | `Stats`           |
+//! | `DELETE` | `/activities/{id}`          |                                       | `{"deleted"}`     |
+//! | `POST`   | `/activities/{id}/archive`  |                                       | `{"archived"}`    |
+//! | `POST`   | `/activities/{id}/restore`  |                                       | `Stats`           |
+//! | `PUT`    | `/activities/{id}/assigned` | `{"assigned"}`                        | `[TreeNode]`      |
+//! | `POST`   | `/activities/{id}/sessions` | `{"duration", "timestamp"?}`          | `SessionReport`   |
+//! | `GET`    | `/sessions`                 | query: `from`, `to`, `subtree`        | `[SessionReport]` |
+//! | `GET`    | `/timer`                    |                                       | `TimerReport?`    |
+//! | `POST`   | `/timer/start`              | `{"id"}`                              | `TimerReport`     |
+//! | `POST`   | `/timer/stop`               |                                       | `SessionReport?`  |
+//!
+//! Changes can be undone like any other, with `acts undo`.
+//!
+//! Setting `assigned` rescales the siblings to share the rest, and answers with the new
+//! values of the whole sibling group. Session durations are in minutes.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::activity::Activity;
+use crate::export::Filter;
+use crate::history::Session;
+use crate::report::{Ranked, SessionReport, Stats, TimerReport, TreeNode};
+use crate::timer::Timer;
+use crate::ActID;
+use crate::Conn;
+
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: 404,
+            message: message.into(),
+        }
+    }
+
+    fn internal(error: rusqlite::Error) -> Self {
+        Self {
+            status: 500,
+            message: error.to_string(),
+        }
+    }
+}
+
+type ApiResult = Result<(u16, Value), ApiError>;
+
+#[derive(Deserialize)]
+struct NewActivity {
+    text: String,
+    parent: Option<ActID>,
+}
+
+#[derive(Deserialize)]
+struct SetAssigned {
+    assigned: u32,
+}
+
+#[derive(Deserialize)]
+struct NewSession {
+    duration: f64,
+    timestamp: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct StartTimer {
+    id: ActID,
+}
+
+pub fn run(conn: &Conn, addr: &str) -> Result<(), String> {
+    let server = tiny_http::Server::http(addr).map_err(|e| e.to_string())?;
+    println!("Listening on http://{}", addr);
+
+    for mut request in server.incoming_requests() {
+        Activity::normalize_assignments(conn);
+        let result = if *request.method() == tiny_http::Method::Get {
+            handle(conn, &mut request)
+        } else {
+            let label = format!("{} {}", request.method(), request.url());
+            crate::journal::record(conn, &label, || handle(conn, &mut request))
+        };
+
+        let (status, body) = match result {
+            Ok((status, body)) => (status, body),
+            Err(e) => (e.status, json!({ "error": e.message })),
+        };
+
+        let header =
+            tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+        let response = tiny_http::Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(header);
+        let _ = request.respond(response);
+    }
+
+    Ok(())
+}
+
+fn handle(conn: &Conn, request: &mut tiny_http::Request) -> ApiResult {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+    use tiny_http::Method::*;
+    let result = match (request.method(), segments.as_slice()) {
+        (Get, ["activities"]) => ok(TreeNode::roots(conn)),
+        (Get, ["activities", "ranked"]) => ok(Ranked::all(conn)),
+        (Post, ["activities"]) => create_activity(conn, parse(&body)?),
+        (Get, ["activities", id]) => ok(Stats::new(conn, find(conn, id)?)),
+        (Patch, ["activities", id]) => update_activity(conn, find(conn, id)?, parse(&body)?),
+        (Delete, ["activities", id]) => {
+            let id = find(conn, id)?;
+            Activity::delete_activity(conn, id);
+            ok(json!({ "deleted": id }))
+        }
+        (Post, ["activities", id, "archive"]) => {
+            let id = find(conn, id)?;
+            Activity::archive(conn, id);
+            ok(json!({ "archived": id }))
+        }
+        (Post, ["activities", id, "restore"]) => {
+            let id = find(conn, id)?;
+            Activity::restore(conn, id);
+            ok(Stats::new(conn, id))
+        }
+        (Put, ["activities", id, "assigned"]) => {
+            let id = find(conn, id)?;
+            let body: SetAssigned = parse(&body)?;
+            if body.assigned > 100 {
+                return Err(ApiError::bad_request("assigned can't be more than 100"));
+            }
+            Activity::assign(conn, id, body.assigned);
+            Activity::normalize_assignments(conn);
+            let parent = Activity::get_parent_index(conn, id);
+            ok(Activity::fetch_children(conn, parent)
+                .into_iter()
+                .map(|act| TreeNode::new(conn, act))
+                .collect::<Vec<_>>())
+        }
+        (Post, ["activities", id, "sessions"]) => {
+            let id = find(conn, id)?;
+            let body: NewSession = parse(&body)?;
+            let duration = std::time::Duration::try_from_secs_f64(body.duration * 60.)
+                .ok()
+                .filter(|duration| !duration.is_zero())
+                .ok_or_else(|| {
+                    ApiError::bad_request("duration has to be a positive number of minutes")
+                })?;
+            let timestamp = body
+                .timestamp
+                .unwrap_or_else(|| crate::utils::current_unix().as_secs());
+            if i64::try_from(timestamp).is_err() {
+                return Err(ApiError::bad_request("timestamp is out of range"));
+            }
+            if timestamp > crate::utils::current_unix().as_secs() {
+                return Err(ApiError::bad_request("timestamp is in the future"));
+            }
+            Session::log(conn, id, body.duration, timestamp);
+            let session = Session {
+                id,
+                duration,
+                timestamp,
+            };
+            Ok((201, json!(SessionReport::new(conn, &session))))
+        }
+        (Get, ["sessions"]) => ok(SessionReport::all(conn, &filter(conn, query)?)),
+        (Get, ["timer"]) => ok(TimerReport::running(conn)),
+        (Post, ["timer", "start"]) => {
+            let body: StartTimer = parse(&body)?;
+            find(conn, &body.id.to_string())?;
+            Timer::start(conn, body.id);
+            ok(TimerReport::running(conn))
+        }
+        (Post, ["timer", "stop"]) => {
+            ok(Timer::stop(conn).map(|session| SessionReport::new(conn, &session)))
+        }
+        _ => Err(ApiError::not_found(format!(
+            "No route for {} {}",
+            request.method(),
+            path
+        ))),
+    };
+
+    Activity::normalize_assignments(conn);
+    crate::vault::sync(conn);
+    result
+}
+
+fn ok<T: serde::Serialize>(value: T) -> ApiResult {
+    Ok((200, json!(value)))
+}
+
+fn parse<T: serde::de::DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::bad_request(format!("Invalid body: {}", e)))
+}
+
+fn find(conn: &Conn, id: &str) -> Result<ActID, ApiError> {
+    let id = uuid::Uuid::parse_str(id)
+        .map_err(|_| ApiError::bad_request(format!("Invalid id: {}", id)))?;
+    Activity::fetch_activity(conn, id)
+        .map(|act| act.id)
+        .map_err(|_| ApiError::not_found(format!("No activity with id {}", id)))
+}
+
+fn create_activity(conn: &Conn, body: NewActivity) -> ApiResult {
+    if body.text.trim().is_empty() {
+        return Err(ApiError::bad_request("text can't be empty"));
+    }
+    if let Some(parent) = body.parent {
+        find(conn, &parent.to_string())?;
+    }
+
+    let activity = Activity::new(body.text.trim().to_string(), body.parent);
+    crate::sql::new_activity(conn, &activity).map_err(ApiError::internal)?;
+    Activity::normalize_assignments(conn);
+    Ok((201, json!(Stats::new(conn, activity.id))))
+}
+
+fn update_activity(conn: &Conn, id: ActID, body: Value) -> ApiResult {
+    let body = body
+        .as_object()
+        .ok_or_else(|| ApiError::bad_request("Body has to be an object"))?;
+
+    let text = match body.get("text") {
+        None => None,
+        Some(Value::String(text)) if !text.trim().is_empty() => Some(text.trim().to_string()),
+        Some(_) => return Err(ApiError::bad_request("text has to be a non-empty string")),
+    };
+
+    let parent = match body.get("parent") {
+        None => None,
+        Some(Value::Null) => Some(None),
+        Some(Value::String(parent)) => {
+            let parent = find(conn, parent)?;
+            if Activity::is_in_subtree(conn, parent, id) {
+                return Err(ApiError::bad_request("Can't move an activity below itself"));
+            }
+            Some(Some(parent))
+        }
+        Some(_) => return Err(ApiError::bad_request("parent has to be an id or null")),
+    };
+
+    if let Some(text) = text {
+        Activity::fetch_activity(conn, id)
+            .map_err(ApiError::internal)?
+            .modify_text(text, conn)
+            .map_err(ApiError::internal)?;
+    }
+    if let Some(parent) = parent {
+        Activity::set_parent(conn, id, parent);
+    }
+
+    Ok((200, json!(Stats::new(conn, id))))
+}
+
+fn filter(conn: &Conn, query: &str) -> Result<Filter, ApiError> {
+    let mut filter = Filter::default();
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let date = || {
+            crate::utils::parse_date(value)
+                .ok_or_else(|| ApiError::bad_request(format!("Invalid date: {}", value)))
+        };
+
+        match key {
+            "from" => filter.from = Some(date()?),
+            "to" => filter.to = Some(date()? + 86400),
+            "subtree" => filter.subtree = Some(find(conn, value)?),
+            _ => return Err(ApiError::bad_request(format!("Unknown parameter: {}", key))),
+        }
+    }
+
+    Ok(filter)
+}
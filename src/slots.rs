@@ -0,0 +1,112 @@
+//! Planned slots: time set aside for an activity ahead of time, like "Piano on Monday
+//! from 18:00 for an hour". Slots don't count as time spent, they're only there to be
+//! exported to a calendar next to the logged sessions.
+
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::sql;
+use crate::ActID;
+use crate::Conn;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot {
+    pub id: Uuid,
+    pub activity: ActID,
+    /// Unix time the slot starts.
+    pub start: u64,
+    pub length: Duration,
+    /// What the time is planned for, which can be left empty.
+    pub title: String,
+}
+
+impl std::convert::TryFrom<&rusqlite::Row<'_>> for Slot {
+    type Error = rusqlite::Error;
+
+    fn try_from(value: &rusqlite::Row) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: Uuid::parse_str(&value.get::<usize, String>(0)?).unwrap(),
+            activity: Uuid::parse_str(&value.get::<usize, String>(1)?).unwrap(),
+            start: value.get(2)?,
+            length: Duration::from_secs(value.get(3)?),
+            title: value.get(4)?,
+        })
+    }
+}
+
+impl Slot {
+    pub fn new(activity: ActID, start: u64, length: Duration, title: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            activity,
+            start,
+            length,
+            title,
+        }
+    }
+
+    /// Unix time the slot ends.
+    pub fn end(&self) -> u64 {
+        self.start + self.length.as_secs()
+    }
+
+    pub fn add(&self, conn: &Conn) {
+        conn.execute(
+            "INSERT INTO slots (id, activity, start, length, title) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                self.id.to_string(),
+                self.activity.to_string(),
+                self.start,
+                self.length.as_secs(),
+                self.title
+            ],
+        )
+        .unwrap();
+    }
+
+    /// Removes the slot, returning whether there was one with the id.
+    pub fn remove(conn: &Conn, id: Uuid) -> bool {
+        conn.execute("DELETE FROM slots WHERE id = ?1", [id.to_string()])
+            .unwrap()
+            > 0
+    }
+
+    /// Every slot, ordered by when it starts.
+    pub fn fetch_all(conn: &Conn) -> Vec<Slot> {
+        let statement = "SELECT s.id, s.activity, s.start, s.length, s.title FROM slots s
+            JOIN activities a ON a.id = s.activity
+            ORDER BY s.start";
+        sql::query_map(conn, statement, |row| Slot::try_from(row)).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Activity;
+
+    #[test]
+    fn test_slots() {
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        sql::new_activity(&conn, &music).unwrap();
+
+        let late = Slot::new(music.id, 5000, Duration::from_secs(3600), String::new());
+        let early = Slot::new(
+            music.id,
+            1000,
+            Duration::from_secs(600),
+            "Scales".to_string(),
+        );
+        late.add(&conn);
+        early.add(&conn);
+        assert_eq!(Slot::fetch_all(&conn), vec![early.clone(), late.clone()]);
+        assert_eq!(early.end(), 1600);
+
+        assert!(Slot::remove(&conn, early.id));
+        assert!(!Slot::remove(&conn, early.id));
+        sql::delete_activity(&conn, music.id);
+        assert!(Slot::fetch_all(&conn).is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! Splitting an activity into new children, like "Music" into "Piano" and "Theory".
+//!
+//! The sessions logged on the activity can be handed to the children so that they don't
+//! start out with no time spent, which would put them at the top of the ranking. A
+//! session that's handed over counts for the child and, like any session, for its
+//! ancestors, so the activity's own total doesn't change.
+
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::sql;
+use crate::ActID;
+use crate::Conn;
+
+/// A child to create, with its initial assigned value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub name: String,
+    pub assigned: u32,
+}
+
+/// How the sessions logged on the split activity are handed to the new children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Distribution {
+    /// Every session is divided by these percentages, one per part, which add up to 100.
+    ///
+    /// Nothing can stay on the activity, since a piece that's as long as one of the
+    /// children's would be taken for that child's copy of it.
+    Percent(Vec<u32>),
+    /// The part each session goes to, in the order of
+    /// [`Session::fetch_direct_sessions_of`]. `None` keeps the session on the activity.
+    Sessions(Vec<Option<usize>>),
+}
+
+impl Distribution {
+    /// Divides every session by the parts' assigned values.
+    pub fn by_assigned(parts: &[Part]) -> Self {
+        let nums = parts.iter().map(|part| part.assigned as i32).collect();
+        let percents = crate::utils::normalize_vec(nums, 100);
+        Distribution::Percent(percents.into_iter().map(|p| p.max(0) as u32).collect())
+    }
+}
+
+/// Parses parts written like `Piano=60, Theory=40`. Parts without a value share what's
+/// left of 100 evenly.
+pub fn parse_parts(s: &str) -> Result<Vec<Part>, String> {
+    let mut parts = vec![];
+    for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let (name, assigned) = match part.split_once('=') {
+            Some((name, assigned)) => {
+                let assigned = assigned
+                    .trim()
+                    .trim_end_matches('%')
+                    .parse::<u32>()
+                    .map_err(|_| format!("Not a percentage: {}", assigned.trim()))?;
+                (name.trim(), Some(assigned))
+            }
+            None => (part, None),
+        };
+        parts.push((name.to_string(), assigned));
+    }
+
+    Ok(fill_assigned(parts))
+}
+
+/// Parses percentages written like `60, 40`.
+pub fn parse_percents(s: &str) -> Result<Vec<u32>, String> {
+    s.split(',')
+        .map(|percent| percent.trim().trim_end_matches('%'))
+        .map(|percent| {
+            percent
+                .parse::<u32>()
+                .map_err(|_| format!("Not a percentage: {}", percent))
+        })
+        .collect()
+}
+
+/// Makes parts of names with optional assigned values. The parts without one share what's
+/// left of 100 evenly.
+pub fn fill_assigned(parts: Vec<(String, Option<u32>)>) -> Vec<Part> {
+    let unassigned = parts.iter().filter(|(_, a)| a.is_none()).count() as u32;
+    let rest = 100u32.saturating_sub(parts.iter().filter_map(|(_, a)| *a).sum());
+    parts
+        .into_iter()
+        .map(|(name, assigned)| Part {
+            name,
+            assigned: assigned.unwrap_or(rest / unassigned.max(1)),
+        })
+        .collect()
+}
+
+/// What the journal calls the split, like `Split Music into Piano, Theory`.
+pub fn label(conn: &Conn, id: ActID, parts: &[Part]) -> String {
+    let names: Vec<&str> = parts.iter().map(|part| part.name.as_str()).collect();
+    format!(
+        "Split {} into {}",
+        Activity::path(conn, id),
+        names.join(", ")
+    )
+}
+
+/// Checks that the parts can be created below the activity and that the distribution fits
+/// them and the activity's sessions.
+pub fn check(
+    conn: &Conn,
+    id: ActID,
+    parts: &[Part],
+    distribution: &Distribution,
+) -> Result<(), String> {
+    if parts.is_empty() {
+        return Err("Give at least one part to split into".to_string());
+    }
+    let existing = Activity::fetch_all_children(conn, Some(id));
+    for (idx, part) in parts.iter().enumerate() {
+        if part.name.trim().is_empty() {
+            return Err("A part has no name".to_string());
+        }
+        if parts[..idx].iter().any(|other| other.name == part.name)
+            || existing.iter().any(|kid| kid.text == part.name)
+        {
+            return Err(format!("There's already a child called {}", part.name));
+        }
+    }
+
+    match distribution {
+        Distribution::Percent(percents) => {
+            if percents.len() != parts.len() {
+                return Err(format!(
+                    "Expected {} percentages, one per part",
+                    parts.len()
+                ));
+            }
+            if percents.iter().sum::<u32>() != 100 {
+                return Err("The percentages have to add up to 100".to_string());
+            }
+        }
+        Distribution::Sessions(owners) => {
+            let sessions = Session::fetch_direct_rows_of(conn, id).len();
+            if owners.len() != sessions {
+                return Err(format!(
+                    "Expected a part for each of the {} sessions",
+                    sessions
+                ));
+            }
+            if owners.iter().flatten().any(|part| *part >= parts.len()) {
+                return Err("A session is given to a part that doesn't exist".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Creates the parts as children of the activity and hands them its sessions. Returns
+/// the ids of the new children, in the order of the parts.
+pub fn split(
+    conn: &Conn,
+    id: ActID,
+    parts: &[Part],
+    distribution: &Distribution,
+) -> Result<Vec<ActID>, String> {
+    check(conn, id, parts, distribution)?;
+
+    let sessions = Session::fetch_direct_rows_of(conn, id);
+    let kids: Vec<ActID> = parts
+        .iter()
+        .map(|part| {
+            let kid = Activity::new(part.name.trim().to_string(), Some(id));
+            sql::new_activity(conn, &kid).unwrap();
+            sql::set_assigned(conn, kid.id, part.assigned);
+            kid.id
+        })
+        .collect();
+
+    match distribution {
+        Distribution::Percent(percents) => {
+            for (minutes, timestamp) in sessions {
+                Session::unlog(conn, id, minutes, timestamp);
+                for (kid, percent) in kids.iter().zip(percents) {
+                    if *percent > 0 {
+                        Session::log(conn, *kid, minutes * *percent as f64 / 100., timestamp);
+                    }
+                }
+            }
+        }
+        Distribution::Sessions(owners) => {
+            for ((minutes, timestamp), owner) in sessions.into_iter().zip(owners) {
+                if let Some(part) = owner {
+                    // The activity and its ancestors already have the session.
+                    conn.execute(
+                        "INSERT INTO history (id, duration, timestamp) VALUES (?1, ?2, ?3)",
+                        rusqlite::params![kids[*part].to_string(), minutes, timestamp],
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    Activity::normalize_assignments(conn);
+    Ok(kids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let conn = sql::init_in_memory();
+        let music = Activity::new("Music".to_string(), None);
+        sql::new_activity(&conn, &music).unwrap();
+        Session::log(&conn, music.id, 60., 1000);
+        Session::log(&conn, music.id, 30., 2000);
+
+        let parts = parse_parts("Piano=75, Theory").unwrap();
+        assert_eq!(parts[1].assigned, 25);
+        assert!(split(
+            &conn,
+            music.id,
+            &parts,
+            &Distribution::Percent(vec![60, 60])
+        )
+        .is_err());
+
+        let total = |id| -> f64 {
+            Session::get_history(&conn, id)
+                .iter()
+                .map(|s| s.minutes())
+                .sum()
+        };
+
+        // Piano gets the first session, the second one stays on Music.
+        let distribution = Distribution::Sessions(vec![Some(0), None]);
+        let kids = split(&conn, music.id, &parts, &distribution).unwrap();
+        assert_eq!(total(kids[0]).round(), 60.);
+        assert_eq!(total(kids[1]).round(), 0.);
+        assert_eq!(total(music.id).round(), 90.);
+        assert_eq!(Session::fetch_direct_sessions_of(&conn, music.id).len(), 1);
+        assert_eq!(
+            Activity::fetch_activity(&conn, kids[0]).unwrap().assigned,
+            75
+        );
+
+        // What stayed on Music is divided between two more parts.
+        let parts = parse_parts("Ear training, Singing").unwrap();
+        let kids = split(&conn, music.id, &parts, &Distribution::by_assigned(&parts)).unwrap();
+        assert_eq!(total(kids[0]).round(), 15.);
+        assert_eq!(total(kids[1]).round(), 15.);
+        assert_eq!(total(music.id).round(), 90.);
+        assert!(Session::fetch_direct_sessions_of(&conn, music.id).is_empty());
+    }
+}
@@ -2,7 +2,6 @@ use crate::activity::Activity;
 use crate::ActID;
 use crate::Conn;
 
-
 pub fn get_kid_qty(conn: &Conn, parent: &Option<ActID>) -> usize {
     let statement = match parent {
         Some(id) => {
@@ -23,43 +22,126 @@ pub fn execute(conn: &Conn, statement: &str) -> Result<(), rusqlite::Error> {
 pub fn delete_activity(conn: &Conn, id: ActID) {
     let statement = format!("DELETE FROM activities WHERE id = '{}'", id);
     execute(conn, &statement).unwrap();
+    let statement = format!("DELETE FROM slots WHERE activity = '{}'", id);
+    execute(conn, &statement).unwrap();
+    set_collapsed(conn, id, false);
 }
 
+/// Sets the assigned value, and the time of the change if it's a different one, as
+/// normalizing sets them all again.
 pub fn set_assigned(conn: &Conn, id: ActID, assigned: u32) {
     let statement = format!(
-        "UPDATE activities SET assigned = '{}' WHERE id = '{}'",
-        assigned, id
+        "UPDATE activities SET assigned = '{0}',
+            updated_at = CASE WHEN assigned = {0} THEN updated_at ELSE {1} END
+            WHERE id = '{2}'",
+        assigned,
+        crate::utils::current_unix().as_secs(),
+        id
     );
     execute(conn, &statement).unwrap();
 }
 
-fn get_db_path() -> std::path::PathBuf{
+/// Sets where the activity goes among its siblings, if it's a different place.
+pub fn set_position(conn: &Conn, id: ActID, position: u32) {
+    let statement = format!(
+        "UPDATE activities SET position = {0} WHERE id = '{1}' AND position != {0}",
+        position, id
+    );
+    execute(conn, &statement).unwrap();
+}
 
+pub fn set_archived(conn: &Conn, id: ActID, archived: bool) {
+    let statement = format!(
+        "UPDATE activities SET archived = {}, updated_at = {} WHERE id = '{}'",
+        archived as u8,
+        crate::utils::current_unix().as_secs(),
+        id
+    );
+    execute(conn, &statement).unwrap();
+}
+
+/// The activities whose children are hidden in the tree views.
+pub fn fetch_collapsed(conn: &Conn) -> std::collections::HashSet<ActID> {
+    query_map(conn, "SELECT id FROM collapsed", |row| {
+        Ok(uuid::Uuid::parse_str(&row.get::<usize, String>(0)?).unwrap())
+    })
+    .unwrap()
+    .into_iter()
+    .collect()
+}
+
+pub fn set_collapsed(conn: &Conn, id: ActID, collapsed: bool) {
+    let statement = if collapsed {
+        format!("INSERT OR IGNORE INTO collapsed (id) VALUES ('{}')", id)
+    } else {
+        format!("DELETE FROM collapsed WHERE id = '{}'", id)
+    };
+    execute(conn, &statement).unwrap();
+}
+
+/// The directory where acts keeps its database, notes and exports.
+pub fn data_dir() -> std::path::PathBuf {
     let mut file_path = std::path::PathBuf::new();
 
     if let Some(home_dir) = dirs::home_dir() {
         file_path.push(home_dir);
         file_path.push(".local/share/acts/");
         std::fs::create_dir_all(&file_path).expect("Failed to create acts directory");
-        file_path.push("mydb.db");
-        return file_path
-    } else {panic!()}
+        file_path
+    } else {
+        panic!()
+    }
 }
 
+fn get_db_path() -> std::path::PathBuf {
+    let file_path = crate::config::get().db_path();
+    if let Some(dir) = file_path.parent() {
+        std::fs::create_dir_all(dir).expect("Failed to create database directory");
+    }
+    file_path
+}
 
 pub fn init() -> Conn {
     let path = get_db_path();
     let conn = std::rc::Rc::new(rusqlite::Connection::open(path).unwrap());
+    create_tables(&conn);
+    crate::notes::migrate_files(&conn);
+    conn
+}
+
+/// A fresh database that only lives as long as the connection, for tests.
+#[cfg(test)]
+pub fn init_in_memory() -> Conn {
+    let conn = std::rc::Rc::new(rusqlite::Connection::open_in_memory().unwrap());
+    create_tables(&conn);
+    conn
+}
 
+pub fn create_tables(conn: &Conn) {
     let statement = "CREATE TABLE IF NOT EXISTS activities (
             id TEXT NOT NULL,
             text TEXT NOT NULL,
             parent TEXT,
             assigned INTEGER NOT NULL,
+            archived INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL DEFAULT 0,
+            updated_at INTEGER NOT NULL DEFAULT 0,
+            last_session_at INTEGER,
+            position INTEGER NOT NULL DEFAULT 0,
             FOREIGN KEY (parent) REFERENCES activities (id)
             )
             ";
-    execute(&conn, statement).unwrap();
+    execute(conn, statement).unwrap();
+    add_column(conn, "activities", "archived", "INTEGER NOT NULL DEFAULT 0");
+    if add_column(conn, "activities", "position", "INTEGER NOT NULL DEFAULT 0") {
+        // Keep the order they were shown in before, which was mostly the order they were added.
+        execute(
+            conn,
+            "UPDATE activities SET position = (SELECT COUNT(*) FROM activities a
+                WHERE a.parent IS activities.parent AND a.rowid < activities.rowid)",
+        )
+        .unwrap();
+    }
 
     let statement = "CREATE TABLE IF NOT EXISTS history (
             id INTEGER,
@@ -68,9 +150,174 @@ pub fn init() -> Conn {
             FOREIGN KEY (id) REFERENCES activities (id)
             )
             ";
-    execute(&conn, statement).unwrap();
+    execute(conn, statement).unwrap();
+    add_timestamps(conn);
 
-    conn
+    let statement = "CREATE TABLE IF NOT EXISTS timer (
+            id TEXT NOT NULL,
+            started INTEGER NOT NULL,
+            FOREIGN KEY (id) REFERENCES activities (id)
+            )
+            ";
+    execute(conn, statement).unwrap();
+
+    let statement = "CREATE TABLE IF NOT EXISTS notes (
+            id TEXT PRIMARY KEY,
+            note TEXT NOT NULL,
+            FOREIGN KEY (id) REFERENCES activities (id)
+            )
+            ";
+    execute(conn, statement).unwrap();
+
+    let statement = "CREATE TABLE IF NOT EXISTS journal (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            label TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            undone INTEGER NOT NULL DEFAULT 0
+            )
+            ";
+    execute(conn, statement).unwrap();
+
+    let statement = "CREATE TABLE IF NOT EXISTS slots (
+            id TEXT PRIMARY KEY,
+            activity TEXT NOT NULL,
+            start INTEGER NOT NULL,
+            length INTEGER NOT NULL,
+            title TEXT NOT NULL DEFAULT '',
+            FOREIGN KEY (activity) REFERENCES activities (id)
+            )
+            ";
+    execute(conn, statement).unwrap();
+
+    let statement = "CREATE TABLE IF NOT EXISTS collapsed (
+            id TEXT PRIMARY KEY,
+            FOREIGN KEY (id) REFERENCES activities (id)
+            )
+            ";
+    execute(conn, statement).unwrap();
+
+    let statement = "CREATE TABLE IF NOT EXISTS vault_files (
+            id TEXT PRIMARY KEY,
+            path TEXT NOT NULL,
+            note TEXT NOT NULL
+            )
+            ";
+    execute(conn, statement).unwrap();
+    add_column(conn, "vault_files", "content", "TEXT NOT NULL DEFAULT ''");
+    add_column(conn, "vault_files", "modified", "INTEGER");
+
+    create_search_index(conn);
+}
+
+/// Adds the activities' timestamps to a database from before they existed, and the
+/// triggers that keep `last_session_at` up to date however the history changes.
+///
+/// Old activities count as created when their first session ended, or now if they have
+/// none.
+fn add_timestamps(conn: &Conn) {
+    if add_column(
+        conn,
+        "activities",
+        "created_at",
+        "INTEGER NOT NULL DEFAULT 0",
+    ) {
+        let statement = format!(
+            "UPDATE activities SET created_at = IFNULL(
+                (SELECT MIN(timestamp) FROM history WHERE history.id = activities.id), {})",
+            crate::utils::current_unix().as_secs()
+        );
+        execute(conn, &statement).unwrap();
+    }
+    if add_column(
+        conn,
+        "activities",
+        "updated_at",
+        "INTEGER NOT NULL DEFAULT 0",
+    ) {
+        execute(conn, "UPDATE activities SET updated_at = created_at").unwrap();
+    }
+    if add_column(conn, "activities", "last_session_at", "INTEGER") {
+        execute(
+            conn,
+            "UPDATE activities SET last_session_at =
+                (SELECT MAX(timestamp) FROM history WHERE history.id = activities.id)",
+        )
+        .unwrap();
+    }
+
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS last_session_insert AFTER INSERT ON history BEGIN
+            UPDATE activities SET last_session_at = MAX(IFNULL(last_session_at, 0), new.timestamp)
+                WHERE id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS last_session_delete AFTER DELETE ON history BEGIN
+            UPDATE activities SET last_session_at =
+                (SELECT MAX(timestamp) FROM history WHERE id = old.id)
+                WHERE id = old.id;
+        END;",
+    )
+    .unwrap();
+}
+
+/// Adds a column to a table that was created before the column existed. Returns whether
+/// it was added.
+fn add_column(conn: &Conn, table: &str, column: &str, definition: &str) -> bool {
+    let columns = query_map(conn, &format!("PRAGMA table_info({})", table), |row| {
+        row.get::<usize, String>(1)
+    })
+    .unwrap();
+    if !columns.iter().any(|name| name == column) {
+        let statement = format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition);
+        execute(conn, &statement).unwrap();
+        return true;
+    }
+    false
+}
+
+/// The full-text index of activity names and notes, see `search`. It's kept up to date
+/// by triggers, and filled from the tables when it's first created.
+fn create_search_index(conn: &Conn) {
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'search')",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    if exists {
+        return;
+    }
+
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE search USING fts5 (id UNINDEXED, text, note);
+
+        INSERT INTO search (id, text, note)
+            SELECT activities.id, activities.text, notes.note
+            FROM activities LEFT JOIN notes ON notes.id = activities.id;
+
+        CREATE TRIGGER search_activity_insert AFTER INSERT ON activities BEGIN
+            INSERT INTO search (id, text, note)
+                VALUES (new.id, new.text, (SELECT note FROM notes WHERE id = new.id));
+        END;
+        CREATE TRIGGER search_activity_update AFTER UPDATE OF text ON activities BEGIN
+            UPDATE search SET text = new.text WHERE id = new.id;
+        END;
+        CREATE TRIGGER search_activity_delete AFTER DELETE ON activities BEGIN
+            DELETE FROM search WHERE id = old.id;
+        END;
+
+        CREATE TRIGGER search_note_insert AFTER INSERT ON notes BEGIN
+            UPDATE search SET note = new.note WHERE id = new.id;
+        END;
+        CREATE TRIGGER search_note_update AFTER UPDATE ON notes BEGIN
+            UPDATE search SET note = new.note WHERE id = new.id;
+        END;
+        CREATE TRIGGER search_note_delete AFTER DELETE ON notes BEGIN
+            UPDATE search SET note = NULL WHERE id = old.id;
+        END;",
+    )
+    .unwrap();
 }
 
 pub fn new_activity(conn: &Conn, activity: &Activity) -> Result<(), rusqlite::Error> {
@@ -78,12 +325,16 @@ pub fn new_activity(conn: &Conn, activity: &Activity) -> Result<(), rusqlite::Er
     //let assigned = if sibqty > 0 { 1. / (sibqty as f32) } else { 1. };
     let assigned = 50;
     conn.execute(
-        "INSERT INTO activities (id, text, parent, assigned) VALUES (?1, ?2, ?3, ?4)",
+        "INSERT INTO activities (id, text, parent, assigned, created_at, updated_at, position)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6,
+                (SELECT IFNULL(MAX(position) + 1, 0) FROM activities WHERE parent IS ?3))",
         (
             activity.id.to_string(),
             &activity.text,
             activity.parent.map(|p| p.to_string()),
             assigned,
+            activity.created_at,
+            activity.updated_at,
         ),
     )?;
 
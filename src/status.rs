@@ -0,0 +1,103 @@
+//! A one-line status for desktop bars, printed by `acts status`.
+//!
+//! It shows the running timer and how long it has been running, or else the leaf at the
+//! top of the priority list with its score. Clicking toggles the timer: a running one is
+//! stopped, otherwise the top leaf is started.
+//!
+//! ```text
+//! # waybar
+//! "custom/acts": {
+//!     "exec": "acts status --format waybar",
+//!     "return-type": "json",
+//!     "interval": 1,
+//!     "on-click": "acts status --toggle"
+//! }
+//!
+//! # polybar
+//! [module/acts]
+//! type = custom/script
+//! exec = acts status
+//! interval = 1
+//! click-left = acts status --toggle
+//!
+//! # i3blocks, clicks are read from $BLOCK_BUTTON
+//! [acts]
+//! command=acts status --format i3blocks
+//! interval=1
+//! ```
+
+use serde::Serialize;
+
+use crate::activity::Activity;
+use crate::report::{Ranked, TimerReport};
+use crate::timer::Timer;
+use crate::Conn;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A single line, for polybar and most other bars.
+    Text,
+    /// A JSON object with `text`, `tooltip`, `class` and `alt`.
+    Waybar,
+    /// The full text and the short text on two lines.
+    I3blocks,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Status {
+    pub text: String,
+    pub tooltip: String,
+    /// `running`, `idle`, or `empty` when there are no activities.
+    pub class: &'static str,
+    /// The name of the activity, without its ancestors.
+    pub alt: String,
+}
+
+impl Status {
+    pub fn new(conn: &Conn) -> Self {
+        if let Some(timer) = TimerReport::running(conn) {
+            let minutes = timer.elapsed as u64;
+            return Self {
+                text: format!("{} {}:{:02}", timer.path, minutes / 60, minutes % 60),
+                tooltip: format!("Timing {}, click to stop", timer.path),
+                class: "running",
+                alt: Activity::fetch_activity(conn, timer.id).unwrap().text,
+            };
+        }
+
+        match Activity::fetch_by_priority(conn).first() {
+            Some(top) => {
+                let top = Ranked::new(conn, top);
+                Self {
+                    text: format!("{} ({:.0})", top.path, top.score),
+                    tooltip: format!("Next up is {}, click to start", top.path),
+                    class: "idle",
+                    alt: top.text,
+                }
+            }
+            None => Self {
+                text: "No activities".to_string(),
+                tooltip: "Add an activity with `acts add`".to_string(),
+                class: "empty",
+                alt: String::new(),
+            },
+        }
+    }
+
+    pub fn print(&self, format: Format) {
+        match format {
+            Format::Text => println!("{}", self.text),
+            Format::Waybar => println!("{}", serde_json::to_string(self).unwrap()),
+            Format::I3blocks => println!("{}\n{}", self.text, self.alt),
+        }
+    }
+}
+
+/// Stops the running timer, or starts timing the top of the priority list.
+pub fn toggle(conn: &Conn) {
+    if Timer::running(conn).is_some() {
+        Timer::stop(conn);
+    } else if let Some(top) = Activity::fetch_by_priority(conn).first() {
+        Timer::start(conn, top.id);
+    }
+}
@@ -0,0 +1,63 @@
+//! A stopwatch for an activity. It's kept in the database so that it keeps running when
+//! the app is closed, and so every front-end sees the same timer.
+
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::ActID;
+use crate::Conn;
+
+#[derive(Debug, Clone)]
+pub struct Timer {
+    pub id: ActID,
+    /// Unix time the timer was started.
+    pub started: u64,
+}
+
+impl Timer {
+    /// The timer that's currently running, if any.
+    pub fn running(conn: &Conn) -> Option<Timer> {
+        conn.query_row("SELECT id, started FROM timer", [], |row| {
+            Ok(Timer {
+                id: uuid::Uuid::parse_str(&row.get::<usize, String>(0)?).unwrap(),
+                started: row.get(1)?,
+            })
+        })
+        .ok()
+    }
+
+    /// Starts timing the activity. A timer that was already running is stopped first, and
+    /// the session it logged is returned.
+    pub fn start(conn: &Conn, id: ActID) -> Option<Session> {
+        let stopped = Self::stop(conn);
+        conn.execute(
+            "INSERT INTO timer (id, started) VALUES (?1, ?2)",
+            rusqlite::params![id.to_string(), crate::utils::current_unix().as_secs()],
+        )
+        .unwrap();
+        stopped
+    }
+
+    /// Stops the running timer and logs the time since it was started. Nothing is logged
+    /// if the activity has been deleted in the meantime, or if no time has passed.
+    pub fn stop(conn: &Conn) -> Option<Session> {
+        let timer = Self::running(conn)?;
+        conn.execute("DELETE FROM timer", []).unwrap();
+
+        Activity::fetch_activity(conn, timer.id).ok()?;
+        let now = crate::utils::current_unix().as_secs();
+        let session = Session {
+            id: timer.id,
+            duration: timer.elapsed(now),
+            timestamp: now,
+        };
+        if session.duration.is_zero() {
+            return None;
+        }
+        Session::log(conn, session.id, session.minutes(), session.timestamp);
+        Some(session)
+    }
+
+    pub fn elapsed(&self, unix_now: u64) -> std::time::Duration {
+        std::time::Duration::from_secs(unix_now.saturating_sub(self.started))
+    }
+}
@@ -0,0 +1,144 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::widgets::{ListItem, Paragraph};
+use ratatui::Frame;
+
+use super::Page;
+use crate::activity::Activity;
+use crate::ActID;
+use crate::Conn;
+use crate::MainMessage;
+
+/// Edits the assigned values of a group of siblings, which have to add up to 100. The
+/// arrow keys rebalance the unlocked others, while typing a value changes only that one.
+pub struct Assignments {
+    activities: Vec<Activity>,
+    /// Which values the arrow keys leave alone. Locks only last while the page is open and
+    /// aren't saved, so [`Activity::normalize_assignments`] scales every sibling alike.
+    locked: Vec<bool>,
+    /// The parent's share of all time in percent, which the siblings divide between them.
+    parent_share: f32,
+    selected: usize,
+}
+
+impl Page for Assignments {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let [diff, list] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Current difference: {} (make it 0 to submit)",
+                self.get_diff()
+            )),
+            diff,
+        );
+
+        let items = self
+            .activities
+            .iter()
+            .zip(&self.locked)
+            .map(|(act, locked)| {
+                ListItem::new(format!(
+                    "{} {:>4}%  {:>5.1}% of all time  {}",
+                    if *locked { "[L]" } else { "[ ]" },
+                    act.assigned,
+                    self.parent_share * act.assigned as f32 / 100.,
+                    act.text
+                ))
+            })
+            .collect();
+        super::draw_list(frame, list, "Assignments", items, self.selected);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        if super::navigate(key.code, &mut self.selected, self.activities.len()) {
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Some(MainMessage::GoBack),
+            KeyCode::Char('a') => {
+                let nums = crate::utils::normalize_unlocked(self.assigned(), &self.locked, 100);
+                self.set_assigned(nums);
+            }
+            KeyCode::Char(' ') if self.selected < self.locked.len() => {
+                self.locked[self.selected] = !self.locked[self.selected];
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') => {
+                if *self.locked.get(self.selected)? {
+                    return None;
+                }
+                let step = match key.code {
+                    KeyCode::Left | KeyCode::Char('h') => -5,
+                    _ => 5,
+                };
+                let value = self.activities[self.selected].assigned as i32 + step;
+                let nums = crate::utils::rebalance(
+                    self.assigned(),
+                    &self.locked,
+                    self.selected,
+                    value.clamp(0, 100),
+                    100,
+                );
+                self.set_assigned(nums);
+            }
+            KeyCode::Enter if self.get_diff() == 0 => {
+                let values = self
+                    .activities
+                    .iter()
+                    .map(|act| (act.id, act.assigned))
+                    .collect();
+                return Some(MainMessage::SetAssigned(values));
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                let act = self.activities.get_mut(self.selected)?;
+                let digit = c.to_digit(10).unwrap();
+                act.assigned = (act.assigned * 10 + digit).min(100);
+            }
+            KeyCode::Backspace => {
+                let act = self.activities.get_mut(self.selected)?;
+                act.assigned /= 10;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn help(&self) -> &'static str {
+        "↑↓ select  ←→ rebalance  space lock  0-9/backspace edit  a auto adjust  enter submit  esc back"
+    }
+}
+
+impl Assignments {
+    pub fn new(conn: &Conn, parent: Option<ActID>) -> Self {
+        let activities = Activity::fetch_children(conn, parent);
+        Self {
+            locked: vec![false; activities.len()],
+            parent_share: parent.map_or(100., |parent| Activity::get_true_assigned(conn, parent)),
+            activities,
+            selected: 0,
+        }
+    }
+
+    fn assigned(&self) -> Vec<i32> {
+        self.activities
+            .iter()
+            .map(|act| act.assigned as i32)
+            .collect()
+    }
+
+    fn set_assigned(&mut self, nums: Vec<i32>) {
+        for (act, num) in self.activities.iter_mut().zip(nums) {
+            act.assigned = num.max(0) as u32;
+        }
+    }
+
+    fn get_diff(&self) -> i32 {
+        self.activities
+            .iter()
+            .map(|act| act.assigned as i32)
+            .sum::<i32>()
+            - 100
+    }
+}
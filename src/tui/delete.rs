@@ -0,0 +1,99 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::Frame;
+
+use super::Page;
+use crate::activity::{Activity, DeleteImpact, DeleteMode};
+use crate::ActID;
+use crate::Conn;
+use crate::MainMessage;
+
+/// Asks how to delete an activity, showing what would go with it.
+pub struct DeleteDialog {
+    id: ActID,
+    path: String,
+    parent: Option<String>,
+    impact: DeleteImpact,
+    /// Whether lifting keeps the activity's own sessions as its parent's.
+    merge_sessions: bool,
+}
+
+impl Page for DeleteDialog {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let impact = self.impact;
+        let mut lines = vec![
+            Line::from(format!(
+                "It has {} descendants, {} sessions ({} logged on it directly) and {} notes.",
+                impact.descendants, impact.sessions, impact.direct_sessions, impact.notes
+            )),
+            Line::from(""),
+            Line::from("l  delete only this, moving its children up"),
+        ];
+        if impact.direct_sessions > 0 {
+            lines.push(Line::from(match &self.parent {
+                Some(parent) if self.merge_sessions => format!(
+                    "m  [x] keep its {} sessions as sessions of {}",
+                    impact.direct_sessions, parent
+                ),
+                Some(parent) => format!(
+                    "m  [ ] keep its {} sessions as sessions of {}",
+                    impact.direct_sessions, parent
+                ),
+                None => format!("   its {} sessions are deleted", impact.direct_sessions),
+            }));
+        }
+        if impact.descendants > 0 {
+            lines.push(Line::from(format!(
+                "s  delete the whole subtree: {} activities, {} sessions and {} notes",
+                impact.descendants + 1,
+                impact.sessions,
+                impact.notes
+            )));
+        }
+
+        let dialog =
+            Paragraph::new(lines).block(Block::bordered().title(format!("Delete {}?", self.path)));
+        frame.render_widget(dialog, area);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        match key.code {
+            KeyCode::Esc => Some(MainMessage::GoBack),
+            KeyCode::Char('l') => Some(MainMessage::DeleteActivity {
+                id: self.id,
+                mode: DeleteMode::Lift {
+                    merge_sessions: self.merge_sessions && self.parent.is_some(),
+                },
+            }),
+            KeyCode::Char('m') => {
+                self.merge_sessions = !self.merge_sessions;
+                None
+            }
+            KeyCode::Char('s') if self.impact.descendants > 0 => {
+                Some(MainMessage::DeleteActivity {
+                    id: self.id,
+                    mode: DeleteMode::Subtree,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "l lift children  m toggle keeping sessions  s whole subtree  esc cancel  ctrl+z undo"
+    }
+}
+
+impl DeleteDialog {
+    pub fn new(conn: &Conn, id: ActID) -> Self {
+        Self {
+            id,
+            path: Activity::path(conn, id),
+            parent: Activity::get_parent(conn, id).map(|parent| parent.text),
+            impact: Activity::delete_impact(conn, id),
+            merge_sessions: true,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::Frame;
+
+use super::Page;
+use crate::activity::Activity;
+use crate::ActID;
+use crate::Conn;
+use crate::MainMessage;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Session,
+    Name,
+    Actions,
+}
+
+pub struct EditPage {
+    activity: Activity,
+    name: String,
+    session_duration: String,
+    focus: Focus,
+    conn: Conn,
+}
+
+impl Page for EditPage {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let [path, timestamps, session, name, actions] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(3),
+        ])
+        .areas(area);
+
+        let field = |title: &'static str, value: &str, focus: Focus| {
+            let (value, style) = if self.focus == focus {
+                (
+                    format!("{}_", value),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )
+            } else {
+                (value.to_string(), Style::default())
+            };
+            Paragraph::new(value).block(Block::bordered().title(title).border_style(style))
+        };
+
+        frame.render_widget(
+            Paragraph::new(Activity::path(&self.conn, self.activity.id)),
+            path,
+        );
+        frame.render_widget(
+            Paragraph::new(self.activity.display_timestamps())
+                .style(Style::default().add_modifier(Modifier::DIM)),
+            timestamps,
+        );
+        frame.render_widget(
+            field(
+                "New session (minutes or 1h30m)",
+                &self.session_duration,
+                Focus::Session,
+            ),
+            session,
+        );
+        frame.render_widget(field("Name", &self.name, Focus::Name), name);
+
+        let actions_text =
+            "d delete  a archive  M merge  S split  c add child  n note  p move  % assignments";
+        frame.render_widget(field("Actions", actions_text, Focus::Actions), actions);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        match key.code {
+            KeyCode::Esc => return Some(MainMessage::GoBack),
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Session => Focus::Name,
+                    Focus::Name => Focus::Actions,
+                    Focus::Actions => Focus::Session,
+                };
+                return None;
+            }
+            _ => {}
+        }
+
+        match self.focus {
+            Focus::Session => match key.code {
+                KeyCode::Enter => {
+                    let minutes = crate::utils::parse_minutes(&self.session_duration)?;
+                    return Some(MainMessage::LogSession {
+                        id: self.activity.id,
+                        minutes,
+                    });
+                }
+                KeyCode::Backspace => {
+                    self.session_duration.pop();
+                }
+                KeyCode::Char(c) => self.session_duration.push(c),
+                _ => {}
+            },
+            Focus::Name => match key.code {
+                KeyCode::Enter if !self.name.trim().is_empty() => {
+                    return Some(MainMessage::RenameActivity {
+                        id: self.activity.id,
+                        text: self.name.trim().to_string(),
+                    });
+                }
+                KeyCode::Backspace => {
+                    self.name.pop();
+                }
+                KeyCode::Char(c) => self.name.push(c),
+                _ => {}
+            },
+            Focus::Actions => {
+                let id = self.activity.id;
+                return match key.code {
+                    KeyCode::Char('d') => Some(MainMessage::NewDelete(id)),
+                    KeyCode::Char('a') => Some(MainMessage::ArchiveActivity(id)),
+                    KeyCode::Char('M') => Some(MainMessage::NewMerge(id)),
+                    KeyCode::Char('S') => Some(MainMessage::NewSplit(id)),
+                    KeyCode::Char('c') => Some(MainMessage::PageAddActivity { parent: Some(id) }),
+                    KeyCode::Char('n') => Some(MainMessage::EditNote { id }),
+                    KeyCode::Char('p') => Some(MainMessage::ChooseParent { child: id }),
+                    KeyCode::Char('%') => Some(MainMessage::NewAssign(id)),
+                    _ => None,
+                };
+            }
+        }
+        None
+    }
+
+    fn help(&self) -> &'static str {
+        "tab next field  enter submit  esc back"
+    }
+}
+
+impl EditPage {
+    pub fn new(conn: Conn, id: ActID) -> Self {
+        let activity = Activity::fetch_activity(&conn, id).unwrap();
+        Self {
+            name: activity.text.clone(),
+            activity,
+            session_duration: String::new(),
+            focus: Focus::Session,
+            conn,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::widgets::ListItem;
+use ratatui::Frame;
+
+use super::Page;
+use crate::activity::{Activity, SortKey};
+use crate::Conn;
+use crate::MainMessage;
+
+/// The leaves in priority order, like the GUI's main view.
+pub struct PriorityList {
+    selected: usize,
+    sort: SortKey,
+    conn: Conn,
+}
+
+impl Page for PriorityList {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let items = Activity::fetch_sorted(&self.conn, self.sort)
+            .iter()
+            .map(|act| {
+                ListItem::new(format!(
+                    "{}{}",
+                    act.display_flat(&self.conn),
+                    crate::notes::marker(&self.conn, act.id)
+                ))
+            })
+            .collect();
+        let title = format!("acts by {}", self.sort.label());
+        super::draw_list(frame, area, &title, items, self.selected);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        let leaves = Activity::fetch_sorted(&self.conn, self.sort);
+        if super::navigate(key.code, &mut self.selected, leaves.len()) {
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Some(MainMessage::GoBack),
+            KeyCode::Char('a') => Some(MainMessage::PageAddActivity { parent: None }),
+            KeyCode::Char('t') => Some(MainMessage::NewTreeView),
+            KeyCode::Char('s') => {
+                self.sort = self.sort.next();
+                None
+            }
+            KeyCode::Enter => leaves
+                .get(self.selected)
+                .map(|act| MainMessage::NewEdit(act.id)),
+            _ => None,
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "↑↓ select  enter edit  a add activity  t tree  s sort  q quit"
+    }
+}
+
+impl PriorityList {
+    pub fn new(conn: Conn) -> Self {
+        Self {
+            selected: 0,
+            sort: SortKey::default(),
+            conn,
+        }
+    }
+}
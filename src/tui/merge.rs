@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, ListItem, Paragraph};
+use ratatui::Frame;
+
+use super::Page;
+use crate::activity::Activity;
+use crate::merge::Preview;
+use crate::ActID;
+use crate::Conn;
+use crate::MainMessage;
+
+/// How many places of the ranking the preview shows.
+const RANKING_LEN: usize = 10;
+
+/// Picks the activity to merge `source` into. Enter shows what the merge would change,
+/// and enter again merges.
+pub struct Merge {
+    source: ActID,
+    selected: usize,
+    preview: Option<(ActID, Result<Preview, String>)>,
+    conn: Conn,
+}
+
+impl Page for Merge {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let title = format!("Merge {} into", Activity::path(&self.conn, self.source));
+        let Some((target, preview)) = &self.preview else {
+            let items = self
+                .candidates()
+                .into_iter()
+                .map(|row| {
+                    ListItem::new(format!("{}{}", "  ".repeat(row.depth), row.activity.text))
+                })
+                .collect();
+            super::draw_list(frame, area, &title, items, self.selected);
+            return;
+        };
+
+        let mut lines = vec![];
+        match preview {
+            Ok(preview) => {
+                lines.push(Line::from("Total time"));
+                for change in &preview.totals {
+                    lines.push(Line::from(format!(
+                        "  {}: {:.0}m -> {:.0}m",
+                        change.path, change.before, change.after
+                    )));
+                }
+                lines.push(Line::from(""));
+                lines.push(Line::from("Ranking now / after merging"));
+                let len = preview
+                    .ranking_before
+                    .len()
+                    .max(preview.ranking_after.len())
+                    .min(RANKING_LEN);
+                for idx in 0..len {
+                    lines.push(Line::from(format!(
+                        "  {}. {:<30} {}",
+                        idx + 1,
+                        preview.ranking_before.get(idx).map_or("", |p| p.as_str()),
+                        preview.ranking_after.get(idx).map_or("", |p| p.as_str())
+                    )));
+                }
+            }
+            Err(e) => lines.push(Line::from(e.clone())),
+        }
+
+        let title = format!("{} {}?", title, Activity::path(&self.conn, *target));
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        if let Some((target, preview)) = &self.preview {
+            return match key.code {
+                KeyCode::Enter if preview.is_ok() => Some(MainMessage::MergeActivity {
+                    source: self.source,
+                    target: *target,
+                }),
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    self.preview = None;
+                    None
+                }
+                _ => None,
+            };
+        }
+
+        let rows = self.candidates();
+        if super::navigate(key.code, &mut self.selected, rows.len()) {
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Some(MainMessage::GoBack),
+            KeyCode::Enter => {
+                let target = rows.get(self.selected)?.activity.id;
+                let preview = crate::merge::preview(&self.conn, self.source, target);
+                self.preview = Some((target, preview));
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        if self.preview.is_some() {
+            "enter merge  esc pick another"
+        } else {
+            "↑↓ select  enter preview  q back"
+        }
+    }
+}
+
+impl Merge {
+    pub fn new(conn: Conn, source: ActID) -> Self {
+        Self {
+            source,
+            selected: 0,
+            preview: None,
+            conn,
+        }
+    }
+
+    /// The tree without the source's subtree, which it can't be merged into.
+    fn candidates(&self) -> Vec<super::TreeRow> {
+        super::tree_rows(&self.conn, &HashSet::from([self.source]), None)
+            .into_iter()
+            .filter(|row| row.activity.id != self.source)
+            .collect()
+    }
+}
@@ -0,0 +1,349 @@
+//! A keyboard driven terminal front-end with the same pages as the GUI.
+//!
+//! It works like the GUI: pages are kept on a stack, the last one is shown and gets the
+//! key presses, and pages ask for anything beyond their own state to be done by
+//! returning a [`MainMessage`].
+
+pub mod assignments;
+pub mod delete;
+pub mod editpage;
+pub mod list;
+pub mod merge;
+pub mod new_activity;
+pub mod picker;
+pub mod split;
+pub mod treeview;
+
+use std::collections::HashSet;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::activity::Activity;
+use crate::journal;
+use crate::ActID;
+use crate::Conn;
+use crate::MainMessage;
+
+pub trait Page {
+    fn draw(&self, frame: &mut Frame, area: Rect);
+
+    /// Handles a key press, returning a message if the app has to do something.
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage>;
+
+    /// The keys the page reacts to, shown at the bottom of the screen.
+    fn help(&self) -> &'static str;
+}
+
+struct Tui {
+    conn: Conn,
+    pages: Vec<Box<dyn Page>>,
+    /// A note to open in the editor, which needs the terminal to itself.
+    edit_note: Option<ActID>,
+    /// Shown instead of the help line until the next key press.
+    error: Option<String>,
+    quit: bool,
+}
+
+impl Tui {
+    fn update(&mut self, message: MainMessage) {
+        match message {
+            MainMessage::GoBack => {
+                self.pages.pop();
+            }
+            MainMessage::PageAddActivity { parent } => {
+                self.pages
+                    .push(Box::new(new_activity::NewActivity::new(parent)));
+            }
+            MainMessage::AddActivity { name, parent } => {
+                let activity = Activity::new(name, parent);
+                journal::record(&self.conn, &format!("Add {}", activity.text), || {
+                    crate::sql::new_activity(&self.conn, &activity).unwrap()
+                });
+                self.pages.pop();
+            }
+            MainMessage::NewEdit(id) => {
+                self.pages
+                    .push(Box::new(editpage::EditPage::new(self.conn.clone(), id)));
+            }
+            MainMessage::NewTreeView => {
+                self.pages
+                    .push(Box::new(treeview::TreeView::new(self.conn.clone())));
+            }
+            MainMessage::NewAssign(id) => {
+                let parent = Activity::get_parent_index(&self.conn, id);
+                self.pages
+                    .push(Box::new(assignments::Assignments::new(&self.conn, parent)));
+            }
+            MainMessage::ChooseParent { child } => {
+                self.pages
+                    .push(Box::new(picker::Picker::new(self.conn.clone(), child)));
+            }
+            MainMessage::SetParent { child, parent } => {
+                if parent.is_none() || parent.unwrap() != child {
+                    let label = format!("Move {}", Activity::path(&self.conn, child));
+                    journal::record(&self.conn, &label, || {
+                        Activity::set_parent(&self.conn, child, parent)
+                    });
+                }
+                self.pages.pop();
+            }
+            MainMessage::Reorder { id, to } => {
+                let label = format!("Move {} {}", Activity::path(&self.conn, id), to.label());
+                journal::record(&self.conn, &label, || Activity::reorder(&self.conn, id, to));
+            }
+            MainMessage::NewDelete(id) => {
+                self.pages
+                    .push(Box::new(delete::DeleteDialog::new(&self.conn, id)));
+            }
+            MainMessage::NewMerge(id) => {
+                self.pages
+                    .push(Box::new(merge::Merge::new(self.conn.clone(), id)));
+            }
+            MainMessage::MergeActivity { source, target } => {
+                let label = format!(
+                    "Merge {} into {}",
+                    Activity::path(&self.conn, source),
+                    Activity::path(&self.conn, target)
+                );
+                let result = journal::record(&self.conn, &label, || {
+                    crate::merge::merge(&self.conn, source, target)
+                });
+                match result {
+                    Ok(()) => {
+                        // The merge page, and the source's edit page.
+                        self.pages.pop();
+                        self.pages.pop();
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            MainMessage::NewSplit(id) => {
+                self.pages.push(Box::new(split::Split::new(&self.conn, id)));
+            }
+            MainMessage::SplitActivity {
+                id,
+                parts,
+                distribution,
+            } => {
+                let label = crate::split::label(&self.conn, id, &parts);
+                let result = journal::record(&self.conn, &label, || {
+                    crate::split::split(&self.conn, id, &parts, &distribution)
+                });
+                match result {
+                    Ok(_) => {
+                        self.pages.pop();
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            MainMessage::DeleteActivity { id, mode } => {
+                let label = format!("Delete {}", Activity::path(&self.conn, id));
+                journal::record(&self.conn, &label, || {
+                    Activity::delete(&self.conn, id, mode)
+                });
+                // The dialog, and the edit page it was opened from.
+                self.pages.pop();
+                self.pages.pop();
+            }
+            MainMessage::ArchiveActivity(id) => {
+                let label = format!("Archive {}", Activity::path(&self.conn, id));
+                journal::record(&self.conn, &label, || Activity::archive(&self.conn, id));
+                self.pages.pop();
+            }
+            MainMessage::RenameActivity { id, text } => {
+                let label = format!("Rename {} to {}", Activity::path(&self.conn, id), text);
+                journal::record(&self.conn, &label, || {
+                    Activity::update_text(&self.conn, id, &text).unwrap()
+                });
+                self.pages.pop();
+            }
+            MainMessage::LogSession { id, minutes } => {
+                let label = format!("Log {}m on {}", minutes, Activity::path(&self.conn, id));
+                let timestamp = crate::utils::current_unix().as_secs();
+                journal::record(&self.conn, &label, || {
+                    crate::history::Session::log(&self.conn, id, minutes, timestamp)
+                });
+                self.pages.pop();
+            }
+            MainMessage::SetAssigned(values) => {
+                journal::record(&self.conn, "Change assignments", || {
+                    for (id, assigned) in values {
+                        crate::sql::set_assigned(&self.conn, id, assigned);
+                    }
+                });
+                self.pages.pop();
+            }
+            MainMessage::Undo => {
+                self.error = Some(match journal::undo(&self.conn) {
+                    Some(label) => format!("Undid: {}", label),
+                    None => "Nothing to undo".to_string(),
+                });
+                // The pages could be showing activities that are gone now.
+                self.pages.truncate(1);
+            }
+            MainMessage::Redo => {
+                self.error = Some(match journal::redo(&self.conn) {
+                    Some(label) => format!("Redid: {}", label),
+                    None => "Nothing to redo".to_string(),
+                });
+                self.pages.truncate(1);
+            }
+            MainMessage::EditNote { id } => self.edit_note = Some(id),
+            MainMessage::Refresh
+            | MainMessage::NewExport { .. }
+            | MainMessage::NewImport { .. }
+            | MainMessage::NewSettings
+            | MainMessage::NewProfiles
+            | MainMessage::NewSearch
+            | MainMessage::NewArchive
+            | MainMessage::SortBy(_)
+            | MainMessage::SwitchProfile(_)
+            | MainMessage::NoOp => {}
+        }
+
+        if self.pages.is_empty() {
+            self.quit = true;
+        }
+        Activity::normalize_assignments(&self.conn);
+        crate::vault::sync(&self.conn);
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [body, help] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let page = self.pages.last().unwrap();
+        page.draw(frame, body);
+        let help_line = match &self.error {
+            Some(error) => Paragraph::new(error.clone()),
+            None => Paragraph::new(page.help()).style(Style::default().add_modifier(Modifier::DIM)),
+        };
+        frame.render_widget(help_line, help);
+    }
+}
+
+pub fn run(conn: &Conn) -> std::io::Result<()> {
+    Activity::normalize_assignments(conn);
+    crate::vault::sync(conn);
+    let mut tui = Tui {
+        conn: conn.clone(),
+        pages: vec![Box::new(list::PriorityList::new(conn.clone()))],
+        edit_note: None,
+        error: None,
+        quit: false,
+    };
+
+    let mut terminal = ratatui::init();
+    let result = (|| {
+        while !tui.quit {
+            terminal.draw(|frame| tui.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                tui.error = None;
+                if let Some(message) = shortcut(key) {
+                    tui.update(message);
+                } else if let Some(message) = tui.pages.last_mut().unwrap().key(key) {
+                    tui.update(message);
+                }
+            }
+
+            if let Some(id) = tui.edit_note.take() {
+                ratatui::restore();
+                tui.error = crate::notes::edit_in_terminal(&tui.conn, id).err();
+                terminal = ratatui::init();
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result
+}
+
+/// Ctrl+Z undoes, Ctrl+Y or Ctrl+Shift+Z redoes, on every page.
+fn shortcut(key: KeyEvent) -> Option<MainMessage> {
+    if !key.modifiers.contains(KeyModifiers::CONTROL) {
+        return None;
+    }
+    match key.code {
+        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::SHIFT) => {
+            Some(MainMessage::Redo)
+        }
+        KeyCode::Char('Z') | KeyCode::Char('y') => Some(MainMessage::Redo),
+        KeyCode::Char('z') => Some(MainMessage::Undo),
+        _ => None,
+    }
+}
+
+/// Moves a selection up or down a list of `len` items for the arrow and vim keys.
+/// Returns whether the key was one of them.
+fn navigate(key: KeyCode, selected: &mut usize, len: usize) -> bool {
+    match key {
+        KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => {
+            *selected = (*selected + 1).min(len.saturating_sub(1))
+        }
+        KeyCode::Home | KeyCode::Char('g') => *selected = 0,
+        KeyCode::End | KeyCode::Char('G') => *selected = len.saturating_sub(1),
+        _ => return false,
+    }
+    true
+}
+
+fn draw_list(frame: &mut Frame, area: Rect, title: &str, items: Vec<ListItem>, selected: usize) {
+    let list = List::new(items)
+        .block(Block::bordered().title(Line::from(title.to_string())))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// A row of the tree as it's shown in the tree view and the picker.
+struct TreeRow {
+    activity: Activity,
+    depth: usize,
+    has_children: bool,
+}
+
+/// The tree flattened in display order, leaving out the descendants of collapsed activities.
+/// With `shown`, only those activities are in it and nothing is collapsed.
+fn tree_rows(
+    conn: &Conn,
+    collapsed: &HashSet<ActID>,
+    shown: Option<&HashSet<ActID>>,
+) -> Vec<TreeRow> {
+    fn recursive(
+        conn: &Conn,
+        collapsed: &HashSet<ActID>,
+        shown: Option<&HashSet<ActID>>,
+        rows: &mut Vec<TreeRow>,
+        parent: Option<ActID>,
+        depth: usize,
+    ) {
+        for kid in Activity::fetch_children(conn, parent) {
+            let id = kid.id;
+            if shown.is_some_and(|shown| !shown.contains(&id)) {
+                continue;
+            }
+            let has_children = !Activity::fetch_children(conn, Some(id)).is_empty();
+            rows.push(TreeRow {
+                activity: kid,
+                depth,
+                has_children,
+            });
+            if shown.is_some() || !collapsed.contains(&id) {
+                recursive(conn, collapsed, shown, rows, Some(id), depth + 1);
+            }
+        }
+    }
+
+    let mut rows = vec![];
+    recursive(conn, collapsed, shown, &mut rows, None, 0);
+    rows
+}
@@ -0,0 +1,52 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::Frame;
+
+use super::Page;
+use crate::ActID;
+use crate::MainMessage;
+
+pub struct NewActivity {
+    input: String,
+    parent: Option<ActID>,
+}
+
+impl Page for NewActivity {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let input = Paragraph::new(format!("{}_", self.input))
+            .block(Block::bordered().title("Add activity"));
+        frame.render_widget(input, area);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        match key.code {
+            KeyCode::Esc => return Some(MainMessage::GoBack),
+            KeyCode::Enter if !self.input.trim().is_empty() => {
+                return Some(MainMessage::AddActivity {
+                    name: self.input.trim().to_string(),
+                    parent: self.parent,
+                })
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) => self.input.push(c),
+            _ => {}
+        }
+        None
+    }
+
+    fn help(&self) -> &'static str {
+        "type a name  enter add  esc back"
+    }
+}
+
+impl NewActivity {
+    pub fn new(parent: Option<ActID>) -> Self {
+        Self {
+            input: String::new(),
+            parent,
+        }
+    }
+}
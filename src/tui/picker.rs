@@ -0,0 +1,123 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::widgets::{ListItem, Paragraph};
+use ratatui::Frame;
+
+use super::Page;
+use crate::activity::Activity;
+use crate::picker::Candidate;
+use crate::ActID;
+use crate::Conn;
+use crate::MainMessage;
+
+/// Picks a new parent for `child`. Typing filters the activities by name, and the first
+/// row stands for the root.
+pub struct Picker {
+    child: ActID,
+    filter: String,
+    selected: usize,
+    conn: Conn,
+}
+
+impl Page for Picker {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let [list, filter, preview] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let candidates = self.candidates();
+        let current = Activity::get_parent_index(&self.conn, self.child);
+        let here = |parent: Option<ActID>| if parent == current { " (current)" } else { "" };
+
+        let mut items = vec![ListItem::new(format!("(move to root){}", here(None)))];
+        items.extend(candidates.iter().map(|candidate| {
+            ListItem::new(format!(
+                "{}{}{}",
+                "  ".repeat(candidate.depth + 1),
+                candidate.label,
+                here(Some(candidate.id))
+            ))
+        }));
+        let title = format!("Move {} under", Activity::path(&self.conn, self.child));
+        super::draw_list(frame, list, &title, items, self.selected);
+
+        frame.render_widget(Paragraph::new(format!("/{}_", self.filter)), filter);
+
+        let parent = self.parent_at(&candidates);
+        let now = Activity::get_true_assigned(&self.conn, self.child);
+        let text = if parent == current {
+            format!("Already there, with {:.1}% of all time", now)
+        } else {
+            format!(
+                "It would get {:.1}% of all time, now {:.1}%",
+                crate::picker::effective_share(&self.conn, self.child, parent),
+                now
+            )
+        };
+        frame.render_widget(Paragraph::new(text), preview);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        let candidates = self.candidates();
+        match key.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(candidates.len()),
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.selected = 0;
+            }
+            KeyCode::Esc => return Some(MainMessage::GoBack),
+            KeyCode::Backspace | KeyCode::Char(_) => {
+                match key.code {
+                    KeyCode::Char(c) => self.filter.push(c),
+                    _ => {
+                        self.filter.pop();
+                    }
+                }
+                // The best guess is the first match rather than the root.
+                self.selected = if self.filter.is_empty() || self.candidates().is_empty() {
+                    0
+                } else {
+                    1
+                };
+            }
+            KeyCode::Enter => {
+                return Some(MainMessage::SetParent {
+                    child: self.child,
+                    parent: self.parent_at(&candidates),
+                })
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn help(&self) -> &'static str {
+        "type to filter  ↑↓ select  enter choose  esc clear/back"
+    }
+}
+
+impl Picker {
+    pub fn new(conn: Conn, child: ActID) -> Self {
+        Self {
+            child,
+            filter: String::new(),
+            selected: 0,
+            conn,
+        }
+    }
+
+    fn candidates(&self) -> Vec<Candidate> {
+        crate::picker::candidates(&self.conn, self.child, &self.filter)
+    }
+
+    fn parent_at(&self, candidates: &[Candidate]) -> Option<ActID> {
+        match self.selected {
+            0 => None,
+            idx => candidates.get(idx - 1).map(|candidate| candidate.id),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, ListItem, Paragraph};
+use ratatui::Frame;
+
+use super::Page;
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::split::Distribution;
+use crate::utils::UtcDateTime;
+use crate::ActID;
+use crate::Conn;
+use crate::MainMessage;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Parts,
+    Percent,
+    Sessions,
+}
+
+/// Splits an activity into new children, typed like `Piano=60, Theory=40`, and hands
+/// them its sessions by percentage or one by one.
+pub struct Split {
+    id: ActID,
+    path: String,
+    parts: String,
+    percent: String,
+    by_session: bool,
+    /// The sessions logged on the activity itself, with the part each one goes to.
+    sessions: Vec<(Session, Option<usize>)>,
+    selected: usize,
+    focus: Focus,
+    problem: Option<String>,
+}
+
+impl Page for Split {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let [path, parts, percent, sessions, problem] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let field = |title: &'static str, value: &str, focus: Focus| {
+            let (value, style) = if self.focus == focus {
+                (
+                    format!("{}_", value),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )
+            } else {
+                (value.to_string(), Style::default())
+            };
+            Paragraph::new(value).block(Block::bordered().title(title).border_style(style))
+        };
+
+        frame.render_widget(Paragraph::new(format!("Split {}", self.path)), path);
+        frame.render_widget(
+            field(
+                "Parts (like Piano=60, Theory=40)",
+                &self.parts,
+                Focus::Parts,
+            ),
+            parts,
+        );
+        if !self.by_session {
+            frame.render_widget(
+                field(
+                    "% of the sessions for each part (empty: as assigned)",
+                    &self.percent,
+                    Focus::Percent,
+                ),
+                percent,
+            );
+        }
+
+        let names = self.part_names();
+        let items = self
+            .sessions
+            .iter()
+            .map(|(session, owner)| {
+                let date = UtcDateTime::from_unix(session.timestamp);
+                let owner = match owner.and_then(|part| names.get(part)) {
+                    Some(name) => name.as_str(),
+                    None => "keep",
+                };
+                ListItem::new(format!(
+                    "{} {}  {:>5.0}m  -> {}",
+                    date.date(),
+                    date.time(),
+                    session.minutes(),
+                    owner
+                ))
+            })
+            .collect();
+        let title = if self.by_session {
+            "[x] Sessions one by one (space)"
+        } else {
+            "[ ] Sessions one by one (space)"
+        };
+        if self.focus == Focus::Sessions {
+            super::draw_list(frame, sessions, title, items, self.selected);
+        } else {
+            frame.render_widget(
+                ratatui::widgets::List::new(items).block(Block::bordered().title(title)),
+                sessions,
+            );
+        }
+
+        if let Some(e) = &self.problem {
+            frame.render_widget(Paragraph::new(e.clone()), problem);
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        self.problem = None;
+        match key.code {
+            KeyCode::Esc => return Some(MainMessage::GoBack),
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Parts if !self.by_session => Focus::Percent,
+                    Focus::Parts | Focus::Percent if !self.sessions.is_empty() => Focus::Sessions,
+                    _ => Focus::Parts,
+                };
+                return None;
+            }
+            KeyCode::Enter => match self.submit() {
+                Ok(message) => return Some(message),
+                Err(e) => {
+                    self.problem = Some(e);
+                    return None;
+                }
+            },
+            _ => {}
+        }
+
+        match self.focus {
+            Focus::Parts | Focus::Percent => {
+                let input = if self.focus == Focus::Parts {
+                    &mut self.parts
+                } else {
+                    &mut self.percent
+                };
+                match key.code {
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    _ => {}
+                }
+            }
+            Focus::Sessions => {
+                if super::navigate(key.code, &mut self.selected, self.sessions.len()) {
+                    return None;
+                }
+                let parts = self.part_names().len();
+                let owner = &mut self.sessions[self.selected].1;
+                match key.code {
+                    KeyCode::Char(' ') => self.by_session = !self.by_session,
+                    // Cycles through keeping the session and each of the parts.
+                    KeyCode::Right | KeyCode::Char('l') => {
+                        *owner = match *owner {
+                            None if parts > 0 => Some(0),
+                            Some(part) if part + 1 < parts => Some(part + 1),
+                            _ => None,
+                        };
+                        self.by_session = true;
+                    }
+                    KeyCode::Left | KeyCode::Char('h') => {
+                        *owner = match *owner {
+                            None if parts > 0 => Some(parts - 1),
+                            Some(part) if part > 0 => Some(part - 1),
+                            _ => None,
+                        };
+                        self.by_session = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    fn help(&self) -> &'static str {
+        match self.focus {
+            Focus::Sessions => {
+                "↑↓ select  ←→ give to part  space one by one  enter split  esc back"
+            }
+            _ => "tab next field  enter split  esc back",
+        }
+    }
+}
+
+impl Split {
+    pub fn new(conn: &Conn, id: ActID) -> Self {
+        Self {
+            id,
+            path: Activity::path(conn, id),
+            parts: String::new(),
+            percent: String::new(),
+            by_session: false,
+            sessions: Session::fetch_direct_sessions_of(conn, id)
+                .into_iter()
+                .map(|session| (session, None))
+                .collect(),
+            selected: 0,
+            focus: Focus::Parts,
+            problem: None,
+        }
+    }
+
+    fn part_names(&self) -> Vec<String> {
+        crate::split::parse_parts(&self.parts)
+            .map(|parts| parts.into_iter().map(|part| part.name).collect())
+            .unwrap_or_default()
+    }
+
+    fn submit(&self) -> Result<MainMessage, String> {
+        let parts = crate::split::parse_parts(&self.parts)?;
+        let distribution = if self.by_session {
+            Distribution::Sessions(self.sessions.iter().map(|(_, owner)| *owner).collect())
+        } else if self.percent.trim().is_empty() {
+            Distribution::by_assigned(&parts)
+        } else {
+            Distribution::Percent(crate::split::parse_percents(&self.percent)?)
+        };
+        Ok(MainMessage::SplitActivity {
+            id: self.id,
+            parts,
+            distribution,
+        })
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::widgets::{ListItem, Paragraph};
+use ratatui::Frame;
+
+use super::Page;
+use crate::activity::{Activity, Reorder};
+use crate::history::Session;
+use crate::ActID;
+use crate::Conn;
+use crate::MainMessage;
+
+pub struct TreeView {
+    selected: usize,
+    /// An activity that was just moved, which stays selected wherever it ends up.
+    follow: Option<ActID>,
+    /// Remembered in the database, so the tree looks the same the next time.
+    collapsed: HashSet<ActID>,
+    /// Shows only the matching activities and their ancestors.
+    filter: String,
+    /// Whether keys are typed into the filter.
+    filtering: bool,
+    conn: Conn,
+}
+
+impl Page for TreeView {
+    fn draw(&self, frame: &mut Frame, area: Rect) {
+        let [list, filter] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(area);
+
+        let rows = self.rows();
+        let selected = self.followed(&rows).unwrap_or(self.selected);
+        let filtered = !self.filter.is_empty();
+        let items = rows
+            .into_iter()
+            .map(|row| {
+                let collapsed = !filtered && self.collapsed.contains(&row.activity.id);
+                let marker = match (row.has_children, collapsed) {
+                    (false, _) => " ",
+                    (true, true) => "▸",
+                    (true, false) => "▾",
+                };
+                ListItem::new(format!(
+                    "{}{} {} [{}%] {}{}",
+                    "  ".repeat(row.depth),
+                    marker,
+                    row.activity.text,
+                    row.activity.assigned,
+                    crate::utils::format_minutes(Session::total_minutes(
+                        &self.conn,
+                        row.activity.id
+                    )),
+                    crate::notes::marker(&self.conn, row.activity.id)
+                ))
+            })
+            .collect();
+        super::draw_list(frame, list, "Tree", items, selected);
+
+        if self.filtering || filtered {
+            let cursor = if self.filtering { "_" } else { "" };
+            frame.render_widget(
+                Paragraph::new(format!("/{}{}", self.filter, cursor)),
+                filter,
+            );
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Option<MainMessage> {
+        if self.filtering {
+            match key.code {
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.filtering = false;
+                }
+                KeyCode::Enter => self.filtering = false,
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => {}
+            }
+            self.selected = 0;
+            return None;
+        }
+
+        let rows = self.rows();
+        if let Some(selected) = self.followed(&rows) {
+            self.selected = selected;
+        }
+        self.follow = None;
+        if super::navigate(key.code, &mut self.selected, rows.len()) {
+            return None;
+        }
+        match key.code {
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                return None;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Some(MainMessage::GoBack),
+            KeyCode::Char('/') => {
+                self.filtering = true;
+                return None;
+            }
+            KeyCode::Char('+') => {
+                self.set_all_collapsed(false);
+                return None;
+            }
+            KeyCode::Char('-') => {
+                self.set_all_collapsed(true);
+                self.selected = 0;
+                return None;
+            }
+            _ => {}
+        }
+
+        let row = rows.get(self.selected)?;
+        let id = row.activity.id;
+
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') if row.has_children => self.set_collapsed(id, true),
+            KeyCode::Right | KeyCode::Char('l') => self.set_collapsed(id, false),
+            KeyCode::Char(' ') if row.has_children => self.toggle(id),
+            KeyCode::Enter => return Some(MainMessage::NewEdit(id)),
+            KeyCode::Char('a') => return Some(MainMessage::PageAddActivity { parent: Some(id) }),
+            KeyCode::Char('p') => return Some(MainMessage::ChooseParent { child: id }),
+            KeyCode::Char('%') => return Some(MainMessage::NewAssign(id)),
+            KeyCode::Char('K') | KeyCode::Char('J') | KeyCode::Char('T') | KeyCode::Char('B') => {
+                let to = match key.code {
+                    KeyCode::Char('K') => Reorder::Up,
+                    KeyCode::Char('J') => Reorder::Down,
+                    KeyCode::Char('T') => Reorder::Top,
+                    _ => Reorder::Bottom,
+                };
+                self.follow = Some(id);
+                return Some(MainMessage::Reorder { id, to });
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn help(&self) -> &'static str {
+        if self.filtering {
+            return "type to filter  enter keep  esc clear";
+        }
+        "↑↓ select  ←→/space collapse  +/- all  / filter  enter edit  a add child  p move  K/J/T/B reorder  % assignments  q back"
+    }
+}
+
+impl TreeView {
+    pub fn new(conn: Conn) -> Self {
+        Self {
+            selected: 0,
+            follow: None,
+            collapsed: crate::sql::fetch_collapsed(&conn),
+            filter: String::new(),
+            filtering: false,
+            conn,
+        }
+    }
+
+    fn followed(&self, rows: &[super::TreeRow]) -> Option<usize> {
+        let id = self.follow?;
+        rows.iter().position(|row| row.activity.id == id)
+    }
+
+    fn rows(&self) -> Vec<super::TreeRow> {
+        if self.filter.is_empty() {
+            super::tree_rows(&self.conn, &self.collapsed, None)
+        } else {
+            let shown = Activity::matching(&self.conn, &self.filter);
+            super::tree_rows(&self.conn, &self.collapsed, Some(&shown))
+        }
+    }
+
+    fn set_collapsed(&mut self, id: ActID, collapsed: bool) {
+        crate::sql::set_collapsed(&self.conn, id, collapsed);
+        self.collapsed = crate::sql::fetch_collapsed(&self.conn);
+    }
+
+    fn toggle(&mut self, id: ActID) {
+        self.set_collapsed(id, !self.collapsed.contains(&id));
+    }
+
+    fn set_all_collapsed(&mut self, collapsed: bool) {
+        let parents: HashSet<ActID> = Activity::fetch_all_activities_flat(&self.conn)
+            .into_iter()
+            .filter_map(|act| act.parent)
+            .collect();
+        for id in parents {
+            crate::sql::set_collapsed(&self.conn, id, collapsed);
+        }
+        self.collapsed = crate::sql::fetch_collapsed(&self.conn);
+    }
+}
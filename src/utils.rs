@@ -4,12 +4,160 @@ pub fn current_unix() -> Duration {
     SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
 }
 
+/// A point in time broken down into its UTC calendar fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcDateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl UtcDateTime {
+    pub fn from_unix(secs: u64) -> Self {
+        let days = (secs / 86400) as i64;
+        let rem = secs % 86400;
+
+        // Howard Hinnant's civil_from_days.
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as u32,
+            minute: (rem % 3600 / 60) as u32,
+            second: (rem % 60) as u32,
+        }
+    }
+
+    /// The ISO 8601 date, e.g. `2023-03-14`.
+    pub fn date(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+
+    /// The ISO 8601 time of day, e.g. `15:09:26`.
+    pub fn time(&self) -> String {
+        format!("{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+/// Days since the unix epoch of a proleptic gregorian date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Parses a `YYYY-MM-DD` date into the unix timestamp of its midnight (UTC).
+pub fn parse_date(s: &str) -> Option<u64> {
+    let mut parts = s.trim().splitn(3, '-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts.next()?.parse::<u32>().ok()?;
+    let day = parts.next()?.parse::<u32>().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    u64::try_from(days * 86400).ok()
+}
+
+/// Parses `YYYY-MM-DD`, optionally followed by a `T` or a space and `HH:MM[:SS]`, as UTC.
+pub fn parse_datetime(s: &str) -> Option<u64> {
+    let s = s.trim().trim_end_matches('Z');
+    let (date, time) = match s.split_once(['T', ' ']) {
+        Some((date, time)) => (date, time),
+        None => (s, "00:00"),
+    };
+
+    let mut parts = time.splitn(3, ':');
+    let hours = parts.next()?.parse::<u64>().ok()?;
+    let minutes = parts.next()?.parse::<u64>().ok()?;
+    let seconds = parts.next().unwrap_or("0").parse::<u64>().ok()?;
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    Some(parse_date(date)? + hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Parses a duration into minutes. Plain numbers are minutes, otherwise numbers are
+/// followed by `h`, `m` or `s`, like `1h30m`.
+pub fn parse_minutes(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if let Ok(minutes) = s.parse::<f64>() {
+        return valid_minutes(minutes);
+    }
+
+    let mut total = 0.;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+
+        let value = number.parse::<f64>().ok()?;
+        number.clear();
+        total += match c {
+            'h' => value * 60.,
+            'm' => value,
+            's' => value / 60.,
+            _ => return None,
+        };
+    }
+
+    number.is_empty().then_some(total).and_then(valid_minutes)
+}
+
+/// Only accepts minutes that fit in a [`std::time::Duration`], so `inf` or
+/// `1e300` never make it into the history.
+fn valid_minutes(minutes: f64) -> Option<f64> {
+    std::time::Duration::try_from_secs_f64(minutes * 60.)
+        .is_ok()
+        .then_some(minutes)
+}
+
+/// Formats minutes the way [`parse_minutes`] reads them, like `1h30m`.
+pub fn format_minutes(minutes: f64) -> String {
+    let minutes = minutes.round() as u64;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{}m", hours, minutes),
+    }
+}
+
 pub fn normalize_vec(nums: Vec<i32>, target: i32) -> Vec<i32> {
     let tot: i32 = nums.iter().sum();
+    if nums.is_empty() {
+        return nums;
+    }
 
     let mut normalized = vec![];
 
-    let diff = target as f32 / tot as f32;
+    // With nothing to scale, they all start at 0 and the target is spread evenly below.
+    let diff = if tot == 0 {
+        0.
+    } else {
+        target as f32 / tot as f32
+    };
     //assert_eq!(diff, 2342.);
     for num in nums {
         normalized.push((num as f32 * diff) as i32);
@@ -30,15 +178,128 @@ pub fn normalize_vec(nums: Vec<i32>, target: i32) -> Vec<i32> {
     normalized
 }
 
-/*
+/// Like [`normalize_vec`], but the locked numbers stay as they are and only the others are
+/// scaled to what's left of the target.
+pub fn normalize_unlocked(nums: Vec<i32>, locked: &[bool], target: i32) -> Vec<i32> {
+    let fixed: i32 = nums
+        .iter()
+        .zip(locked)
+        .filter(|(_, locked)| **locked)
+        .map(|(num, _)| num)
+        .sum();
+    let free: Vec<i32> = nums
+        .iter()
+        .zip(locked)
+        .filter(|(_, locked)| !**locked)
+        .map(|(num, _)| *num)
+        .collect();
+    if free.is_empty() {
+        return nums;
+    }
+
+    let mut free = normalize_vec(free, (target - fixed).max(0)).into_iter();
+    nums.iter()
+        .zip(locked)
+        .map(|(num, locked)| if *locked { *num } else { free.next().unwrap() })
+        .collect()
+}
+
+/// Sets the number at `idx` and rebalances the unlocked others so that they all still add
+/// up to the target. It can't be set higher than what the locked ones leave, and with no
+/// unlocked others it takes all of that.
+pub fn rebalance(nums: Vec<i32>, locked: &[bool], idx: usize, value: i32, target: i32) -> Vec<i32> {
+    let mut nums = nums;
+    let mut locked = locked.to_vec();
+    locked[idx] = false;
+    let room = target
+        - nums
+            .iter()
+            .zip(&locked)
+            .filter(|(_, locked)| **locked)
+            .map(|(num, _)| num)
+            .sum::<i32>();
+
+    let others = locked.iter().filter(|locked| !**locked).count() > 1;
+    nums[idx] = if others { value.min(room) } else { room }.max(0);
+    locked[idx] = true;
+    normalize_unlocked(nums, &locked, target)
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_normalize_vec() {
         let some_vec = vec![5, 3, 2, 1];
-        //        let some_vec = vec![5, 25];
-        let _new_one = normalize_vec(some_vec, 100);
+        let new_one = normalize_vec(some_vec, 100);
+        assert_eq!(new_one.iter().sum::<i32>(), 100);
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let locked = [false, true, false, false];
+        let nums = normalize_unlocked(vec![10, 40, 10, 10], &locked, 100);
+        assert_eq!(nums, vec![20, 40, 20, 20]);
+
+        // The locked 40 stays, the other two share what's left after the 30.
+        let nums = rebalance(nums, &locked, 0, 30, 100);
+        assert_eq!(nums, vec![30, 40, 15, 15]);
+        // It can't take what's locked.
+        let nums = rebalance(nums, &locked, 0, 90, 100);
+        assert_eq!(nums, vec![60, 40, 0, 0]);
+        // With nothing to rebalance against, it takes all that's left.
+        let nums = rebalance(nums, &[false, true, true, true], 0, 10, 100);
+        assert_eq!(nums, vec![60, 40, 0, 0]);
+    }
+
+    #[test]
+    fn test_rebalance_zeros() {
+        assert_eq!(normalize_vec(vec![0, 0, 0], 100), vec![34, 33, 33]);
+        assert!(normalize_vec(vec![], 100).is_empty());
+
+        let locked = [false, true, false, false];
+        let nums = normalize_unlocked(vec![0, 40, 0, 0], &locked, 100);
+        assert_eq!(nums, vec![20, 40, 20, 20]);
+        // The other two are 0, so they split what's left evenly.
+        let nums = rebalance(vec![60, 40, 0, 0], &locked, 0, 30, 100);
+        assert_eq!(nums, vec![30, 40, 15, 15]);
+    }
+
+    #[test]
+    fn test_date_roundtrip() {
+        let unix = parse_date("2023-03-14").unwrap();
+        assert_eq!(unix, 1678752000);
+        assert_eq!(
+            UtcDateTime::from_unix(unix + 3600 * 15 + 60 * 9 + 26),
+            UtcDateTime {
+                year: 2023,
+                month: 3,
+                day: 14,
+                hour: 15,
+                minute: 9,
+                second: 26
+            }
+        );
+        assert_eq!(UtcDateTime::from_unix(unix).date(), "2023-03-14");
+        assert!(parse_date("2023-13-01").is_none());
+        assert_eq!(
+            parse_datetime("2023-03-14T15:09"),
+            Some(unix + 3600 * 15 + 60 * 9)
+        );
+        assert_eq!(parse_datetime("2023-03-14"), Some(unix));
+    }
+
+    #[test]
+    fn test_parse_minutes() {
+        assert_eq!(parse_minutes("45"), Some(45.));
+        assert_eq!(parse_minutes("1h30m"), Some(90.));
+        assert_eq!(parse_minutes("1.5h"), Some(90.));
+        assert_eq!(parse_minutes("90s"), Some(1.5));
+        assert_eq!(parse_minutes("1x"), None);
+        assert_eq!(parse_minutes("1h30"), None);
+        assert_eq!(parse_minutes("inf"), None);
+        assert_eq!(parse_minutes("NaN"), None);
+        assert_eq!(parse_minutes("1e300"), None);
     }
 }
-*/
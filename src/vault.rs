@@ -0,0 +1,425 @@
+//! Mirrors the activities into a folder of Markdown files, like an Obsidian vault, when the
+//! `vault_dir` setting is set.
+//!
+//! Every activity gets a file at its path, e.g. `Music/Piano.md`, with its note as the body:
+//!
+//! ```markdown
+//! ---
+//! id: 5b0e5a48-4ad9-4c1c-a1c1-2f4a8f5e6c01
+//! parent: 0c7b1b0e-6f87-4d3e-9d59-56f7a6b1b2f3
+//! assigned: 40
+//! total_minutes: 754.0
+//! ---
+//!
+//! Practice the Chopin nocturne.
+//! ```
+//!
+//! [`sync`] runs when the app starts and after every action. It takes the body of files
+//! that were edited in the vault as the new note, and then rewrites the files: moving
+//! them after renames and reparents and removing those of deleted activities. The
+//! frontmatter is only written, edits to it are overwritten. Files are matched to
+//! activities by their `id` alone, so moving a file in the vault doesn't lose its note.
+//! Siblings whose names would give the same file, like `a:b` and `a-b`, get their id
+//! added to the file name, e.g. `a-b (5b0e5a48-…).md`.
+//!
+//! Files that acts wrote and nobody touched since, going by their size and modification
+//! time, aren't read again, so the vault is only searched when a file went missing.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::activity::Activity;
+use crate::history::Session;
+use crate::ActID;
+use crate::Conn;
+
+/// Syncs with the configured vault, if there is one.
+pub fn sync(conn: &Conn) {
+    let vault_dir = crate::config::get().vault_dir.clone();
+    if let Some(dir) = vault_dir {
+        if let Err(e) = sync_dir(conn, &dir) {
+            eprintln!("Syncing the vault failed: {}", e);
+        }
+    }
+}
+
+/// What was last written for an activity.
+struct Written {
+    path: PathBuf,
+    /// The body, to tell whether the file was edited since.
+    note: String,
+    content: String,
+    /// The file's modification time right after it was written, in nanoseconds.
+    modified: Option<i64>,
+}
+
+impl Written {
+    /// Whether the file is still the way acts left it, judged by its metadata so that
+    /// it doesn't have to be read.
+    fn untouched(&self, path: &Path) -> bool {
+        self.path == path
+            && self.modified.is_some()
+            && modified(path) == self.modified
+            && std::fs::metadata(path).is_ok_and(|meta| meta.len() == self.content.len() as u64)
+    }
+}
+
+pub fn sync_dir(conn: &Conn, dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    let written = written(conn);
+    let activities = Activity::fetch_all_activities_flat(conn);
+    let found = locate(dir, &written, &activities)?;
+
+    // The edits are all taken in before any file is moved or rewritten, so that a file
+    // that now belongs somewhere else can't be mistaken for another activity's.
+    for activity in &activities {
+        let id = activity.id;
+        let Some(current) = found.get(&id) else {
+            continue;
+        };
+        let previous = written.get(&id);
+        if previous.is_some_and(|previous| previous.untouched(current)) {
+            continue;
+        }
+
+        let body = std::fs::read_to_string(current).map(|s| parse(&s).1)?;
+        let edited = match previous {
+            Some(previous) => previous.note.trim() != body.trim(),
+            // A vault that was set up elsewhere, keep its notes over empty ones.
+            None => crate::notes::read(conn, id).is_none(),
+        };
+        if edited {
+            crate::notes::write(conn, id, &body);
+        }
+    }
+
+    let ours: HashSet<&Path> = found
+        .values()
+        .map(PathBuf::as_path)
+        .chain(written.values().map(|written| written.path.as_path()))
+        .collect();
+    let paths = file_paths(dir, &activities, &ours);
+
+    // Files that moved, and those of deleted activities. Files that acts didn't write
+    // are left alone.
+    let mut removed = vec![];
+    for (id, current) in &found {
+        if paths.get(id).is_some_and(|expected| expected != current) {
+            std::fs::remove_file(current)?;
+            removed.push(current.clone());
+        }
+    }
+    for (id, previous) in &written {
+        if activities.iter().any(|act| act.id == *id) {
+            continue;
+        }
+        let path = found.get(id).unwrap_or(&previous.path);
+        let belongs = std::fs::read_to_string(path).is_ok_and(|s| parse(&s).0 == Some(*id));
+        if belongs && std::fs::remove_file(path).is_ok() {
+            removed.push(path.clone());
+        }
+        conn.execute("DELETE FROM vault_files WHERE id = ?1", [id.to_string()])
+            .unwrap();
+    }
+
+    for activity in &activities {
+        let id = activity.id;
+        let Some(expected) = paths.get(&id) else {
+            continue;
+        };
+        let note = crate::notes::read(conn, id).unwrap_or_default();
+        let content = render(conn, activity, &note);
+        let previous = written.get(&id);
+        if previous
+            .is_some_and(|previous| previous.content == content && previous.untouched(expected))
+        {
+            continue;
+        }
+
+        std::fs::create_dir_all(expected.parent().unwrap())?;
+        std::fs::write(expected, &content)?;
+        conn.execute(
+            "INSERT INTO vault_files (id, path, note, content, modified) VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT (id) DO UPDATE SET path = excluded.path, note = excluded.note,
+                    content = excluded.content, modified = excluded.modified",
+            rusqlite::params![
+                id.to_string(),
+                expected.to_string_lossy(),
+                note,
+                content,
+                modified(expected)
+            ],
+        )
+        .unwrap();
+    }
+
+    for path in removed {
+        remove_empty_dirs(dir, &path);
+    }
+    Ok(())
+}
+
+/// What was last written for each activity.
+fn written(conn: &Conn) -> HashMap<ActID, Written> {
+    let statement = "SELECT id, path, note, content, modified FROM vault_files";
+    crate::sql::query_map(conn, statement, |row| {
+        Ok((
+            uuid::Uuid::parse_str(&row.get::<usize, String>(0)?).unwrap(),
+            Written {
+                path: PathBuf::from(row.get::<usize, String>(1)?),
+                note: row.get(2)?,
+                content: row.get(3)?,
+                modified: row.get(4)?,
+            },
+        ))
+    })
+    .unwrap()
+    .into_iter()
+    .collect()
+}
+
+fn modified(path: &Path) -> Option<i64> {
+    let time = std::fs::metadata(path).ok()?.modified().ok()?;
+    let since = time.duration_since(std::time::UNIX_EPOCH).ok()?;
+    i64::try_from(since.as_nanos()).ok()
+}
+
+/// Where the files of the activities are now, by the id in their frontmatter. The files
+/// acts wrote are checked first, and the whole vault is only searched when one of them
+/// went missing or an activity doesn't have one yet.
+fn locate(
+    dir: &Path,
+    written: &HashMap<ActID, Written>,
+    activities: &[Activity],
+) -> std::io::Result<HashMap<ActID, PathBuf>> {
+    let mut found = HashMap::new();
+    for activity in activities {
+        let Some(previous) = written.get(&activity.id) else {
+            break;
+        };
+        let path = &previous.path;
+        let ours = previous.untouched(path)
+            || std::fs::read_to_string(path).is_ok_and(|s| parse(&s).0 == Some(activity.id));
+        if !ours {
+            break;
+        }
+        found.insert(activity.id, path.clone());
+    }
+
+    if found.len() < activities.len() {
+        found.clear();
+        find_files(dir, written, &mut found)?;
+    }
+    Ok(found)
+}
+
+/// The Markdown files in the vault that have an activity id in their frontmatter. If
+/// there are copies of a file, the one acts wrote wins.
+fn find_files(
+    dir: &Path,
+    written: &HashMap<ActID, Written>,
+    found: &mut HashMap<ActID, PathBuf>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_files(&path, written, found)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some(id) = parse(&content).0 {
+                let ours = written.get(&id).is_some_and(|written| written.path == path);
+                if ours || !found.contains_key(&id) {
+                    found.insert(id, path);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Where each activity's file goes: its path, with one file name per activity, leaving
+/// out characters that aren't allowed in file names. Siblings whose names end up the
+/// same, ignoring case, get their id added to tell them apart, and so do activities whose
+/// file name is taken by a file acts didn't write.
+fn file_paths(
+    dir: &Path,
+    activities: &[Activity],
+    ours: &HashSet<&Path>,
+) -> HashMap<ActID, PathBuf> {
+    fn recursive(
+        kids: &HashMap<Option<ActID>, Vec<&Activity>>,
+        folder: &Path,
+        parent: Option<ActID>,
+        ours: &HashSet<&Path>,
+        paths: &mut HashMap<ActID, PathBuf>,
+    ) {
+        let mut claimed = HashSet::new();
+        for activity in kids.get(&parent).into_iter().flatten() {
+            let name = file_name(&activity.text);
+            let mut path = folder.join(format!("{}.md", name));
+            let foreign = path.exists() && !ours.contains(path.as_path());
+            if foreign || !claimed.insert(name.to_lowercase()) {
+                path = folder.join(format!("{} ({}).md", name, activity.id));
+            }
+            recursive(
+                kids,
+                &path.with_extension(""),
+                Some(activity.id),
+                ours,
+                paths,
+            );
+            paths.insert(activity.id, path);
+        }
+    }
+
+    let mut kids: HashMap<Option<ActID>, Vec<&Activity>> = HashMap::new();
+    for activity in activities {
+        kids.entry(activity.parent).or_default().push(activity);
+    }
+    // The oldest sibling keeps the plain name, so reordering doesn't rename files.
+    for siblings in kids.values_mut() {
+        siblings.sort_by_key(|act| (act.created_at, act.id));
+    }
+
+    let mut paths = HashMap::new();
+    recursive(&kids, dir, None, ours, &mut paths);
+    paths
+}
+
+fn file_name(text: &str) -> String {
+    let name: String = text
+        .chars()
+        .map(|c| if "/\\:*?\"<>|".contains(c) { '-' } else { c })
+        .collect();
+    match name.trim() {
+        "" | "." | ".." => "_".to_string(),
+        name => name.to_string(),
+    }
+}
+
+fn render(conn: &Conn, activity: &Activity, note: &str) -> String {
+    let total_minutes = Session::total_minutes(conn, activity.id);
+    let parent = activity
+        .parent
+        .map(|parent| parent.to_string())
+        .unwrap_or("null".to_string());
+
+    format!(
+        "---\nid: {}\nparent: {}\nassigned: {}\ntotal_minutes: {:.1}\n---\n\n{}",
+        activity.id, parent, activity.assigned, total_minutes, note
+    )
+}
+
+/// The id from the frontmatter, and the body.
+fn parse(content: &str) -> (Option<ActID>, String) {
+    let Some(rest) = content.strip_prefix("---\n") else {
+        return (None, content.to_string());
+    };
+    let Some((frontmatter, body)) = rest.split_once("\n---\n") else {
+        return (None, content.to_string());
+    };
+
+    let id = frontmatter
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim() == "id")
+        .and_then(|(_, value)| uuid::Uuid::parse_str(value.trim()).ok());
+    let body = body.strip_prefix('\n').unwrap_or(body);
+    (id, body.to_string())
+}
+
+/// Removes the directories between `path` and the vault that were left empty.
+fn remove_empty_dirs(vault: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(path) = dir.filter(|dir| dir.starts_with(vault) && *dir != vault) {
+        if std::fs::remove_dir(path).is_err() {
+            break;
+        }
+        dir = path.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync() {
+        let conn = crate::sql::init_in_memory();
+        let dir = std::env::temp_dir().join(format!("acts-vault-{}", uuid::Uuid::new_v4()));
+        let music = Activity::new("Music".to_string(), None);
+        let mut piano = Activity::new("Piano".to_string(), Some(music.id));
+        crate::sql::new_activity(&conn, &music).unwrap();
+        crate::sql::new_activity(&conn, &piano).unwrap();
+        crate::notes::write(&conn, piano.id, "Scales\n");
+
+        sync_dir(&conn, &dir).unwrap();
+        let file = dir.join("Music").join("Piano.md");
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(parse(&content), (Some(piano.id), "Scales\n".to_string()));
+        assert!(content.contains(&format!("parent: {}", music.id)));
+
+        std::fs::write(&file, content.replace("Scales", "Arpeggios")).unwrap();
+        piano.modify_text("Keys".to_string(), &conn).unwrap();
+        sync_dir(&conn, &dir).unwrap();
+        assert_eq!(crate::notes::read(&conn, piano.id).unwrap(), "Arpeggios\n");
+        assert!(!file.exists());
+        assert!(dir.join("Music").join("Keys.md").exists());
+
+        Activity::set_parent(&conn, piano.id, None);
+        sync_dir(&conn, &dir).unwrap();
+        assert!(dir.join("Keys.md").exists());
+        assert!(!dir.join("Music").exists());
+
+        Activity::delete_activity(&conn, piano.id);
+        sync_dir(&conn, &dir).unwrap();
+        assert!(!dir.join("Keys.md").exists());
+        assert!(dir.join("Music.md").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_sync_same_file_names() {
+        let conn = crate::sql::init_in_memory();
+        let dir = std::env::temp_dir().join(format!("acts-vault-{}", uuid::Uuid::new_v4()));
+        let colon = Activity::new("a:b".to_string(), None);
+        let dash = Activity::new("a-b".to_string(), None);
+        crate::sql::new_activity(&conn, &colon).unwrap();
+        crate::sql::new_activity(&conn, &dash).unwrap();
+        crate::notes::write(&conn, colon.id, "Colon\n");
+        crate::notes::write(&conn, dash.id, "Dash\n");
+
+        let file_of = |id| {
+            let mut found = HashMap::new();
+            find_files(&dir, &HashMap::new(), &mut found).unwrap();
+            found.remove(&id).unwrap()
+        };
+        for _ in 0..3 {
+            sync_dir(&conn, &dir).unwrap();
+        }
+        assert_ne!(file_of(colon.id), file_of(dash.id));
+        assert_eq!(crate::notes::read(&conn, colon.id).unwrap(), "Colon\n");
+        assert_eq!(crate::notes::read(&conn, dash.id).unwrap(), "Dash\n");
+
+        let file = file_of(dash.id);
+        let content = std::fs::read_to_string(&file).unwrap();
+        std::fs::write(&file, content.replace("Dash", "Hyphen")).unwrap();
+        sync_dir(&conn, &dir).unwrap();
+        assert_eq!(crate::notes::read(&conn, dash.id).unwrap(), "Hyphen\n");
+        assert_eq!(crate::notes::read(&conn, colon.id).unwrap(), "Colon\n");
+
+        // A file moved in the vault is found by its id and put back.
+        let moved = dir.join("Elsewhere").join("x.md");
+        std::fs::create_dir_all(moved.parent().unwrap()).unwrap();
+        std::fs::rename(file_of(colon.id), &moved).unwrap();
+        sync_dir(&conn, &dir).unwrap();
+        assert!(!moved.exists());
+        assert_eq!(crate::notes::read(&conn, colon.id).unwrap(), "Colon\n");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}